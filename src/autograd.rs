@@ -0,0 +1,324 @@
+//! Imperative gradient computation.
+//!
+//! Operations on `NDArray`s are recorded while a `record` scope is alive, and
+//! `backward` computes the gradients of the recorded results with respect to
+//! the arrays marked with `NDArray::attach_grad`:
+//!
+//! ```ignore
+//! let x = try!(NDArray::from(&[1.0f32, 2.0, 3.0]));
+//! try!(x.attach_grad(GradReq::Write));
+//! let y = {
+//!     let _scope = try!(autograd::record());
+//!     &x * &x
+//! };
+//! try!(y.backward(None, false, true));
+//! let dx = try!(x.grad()).unwrap();
+//! ```
+//!
+//! Functions with gradients implemented in Rust are applied with `apply`.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use libc::{c_int, c_void};
+use mxnet_sys::*;
+
+use custom;
+use ndarray::NDArray;
+use util::*;
+
+/// How gradients are written to the gradient array of a variable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GradReq {
+    /// Do not compute the gradient.
+    Null = 0,
+    /// Overwrite the gradient array.
+    Write = 1,
+    /// Accumulate into the gradient array.
+    Add = 3,
+}
+
+fn set_recording(is_recording: bool) -> MXResult<bool> {
+    let mut prev = 0;
+    c_try!(MXAutogradSetIsRecording(is_recording as c_int, &mut prev));
+    Ok(prev != 0)
+}
+
+fn set_training(is_training: bool) -> MXResult<bool> {
+    let mut prev = 0;
+    c_try!(MXAutogradSetIsTraining(is_training as c_int, &mut prev));
+    Ok(prev != 0)
+}
+
+/// Whether operations are currently being recorded.
+pub fn is_recording() -> MXResult<bool> {
+    let mut curr = false;
+    c_try!(MXAutogradIsRecording(&mut curr));
+    Ok(curr)
+}
+
+/// Whether operators currently run in training mode, e.g. with dropout
+/// enabled.
+pub fn is_training() -> MXResult<bool> {
+    let mut curr = false;
+    c_try!(MXAutogradIsTraining(&mut curr));
+    Ok(curr)
+}
+
+/// Sets the recording and training state, restoring the previous state when
+/// dropped.
+///
+/// The scope must be bound to a named variable; `let _ = autograd::record()`
+/// ends the scope immediately. The state is per thread in mxnet, so scopes
+/// cannot be sent to other threads.
+#[must_use]
+pub struct Scope {
+    prev_recording: Option<bool>,
+    prev_training: Option<bool>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Scope {
+    fn new(is_recording: Option<bool>, is_training: Option<bool>) -> MXResult<Self> {
+        let mut scope = Scope {
+            prev_recording: None,
+            prev_training: None,
+            _not_send: PhantomData,
+        };
+        if let Some(is_recording) = is_recording {
+            scope.prev_recording = Some(try!(set_recording(is_recording)));
+        }
+        if let Some(is_training) = is_training {
+            scope.prev_training = Some(try!(set_training(is_training)));
+        }
+        Ok(scope)
+    }
+}
+
+impl Drop for Scope {
+    // Restoring the state cannot fail short of an mxnet bug, and panicking
+    // here would abort while unwinding, so errors are ignored.
+    fn drop(&mut self) {
+        if let Some(prev) = self.prev_training {
+            let _ = set_training(prev);
+        }
+        if let Some(prev) = self.prev_recording {
+            let _ = set_recording(prev);
+        }
+    }
+}
+
+/// Record operations for `backward` in training mode until the scope is
+/// dropped.
+pub fn record() -> MXResult<Scope> {
+    Scope::new(Some(true), Some(true))
+}
+
+/// Record operations for `backward` in prediction mode until the scope is
+/// dropped.
+pub fn record_predict() -> MXResult<Scope> {
+    Scope::new(Some(true), Some(false))
+}
+
+/// Stop recording operations, in prediction mode, until the scope is dropped.
+pub fn pause() -> MXResult<Scope> {
+    Scope::new(Some(false), Some(false))
+}
+
+/// Stop recording operations, in training mode, until the scope is dropped.
+pub fn pause_train() -> MXResult<Scope> {
+    Scope::new(Some(false), Some(true))
+}
+
+/// Run operators in training mode until the scope is dropped, without
+/// changing whether they are recorded.
+pub fn train_mode() -> MXResult<Scope> {
+    Scope::new(None, Some(true))
+}
+
+/// Run operators in prediction mode until the scope is dropped, without
+/// changing whether they are recorded.
+pub fn predict_mode() -> MXResult<Scope> {
+    Scope::new(None, Some(false))
+}
+
+/// Attach gradient arrays to variables, so that `backward` writes their
+/// gradients according to `grad_reqs`.
+pub fn mark_variables(variables: &[&NDArray],
+                      gradients: &[&NDArray],
+                      grad_reqs: &[GradReq])
+                      -> MXResult<()> {
+    if variables.len() != gradients.len() || variables.len() != grad_reqs.len() {
+        return Err(MXError::new("autograd variables, gradients and grad_reqs size mismatch"));
+    }
+    for (var, grad) in variables.iter().zip(gradients) {
+        try!(var.check_same_shape(grad));
+    }
+    let mut var_handles: Vec<_> = variables.iter().map(|arr| arr.handle).collect();
+    let mut grad_handles: Vec<_> = gradients.iter().map(|arr| arr.handle).collect();
+    let mut reqs: Vec<_> = grad_reqs.iter().map(|req| *req as mx_uint).collect();
+    c_try!(MXAutogradMarkVariables(var_handles.len() as mx_uint,
+                                   var_handles.as_mut_ptr(),
+                                   reqs.as_mut_ptr(),
+                                   grad_handles.as_mut_ptr()));
+    Ok(())
+}
+
+/// Compute the gradients of `heads` with respect to the marked variables.
+///
+/// `head_grads` are the gradients of some loss with respect to each head,
+/// and default to ones. The recorded graph is freed unless `retain_graph` is
+/// set. Operators run in training mode if `train_mode` is set.
+pub fn backward(heads: &[&NDArray],
+                head_grads: Option<&[&NDArray]>,
+                retain_graph: bool,
+                train_mode: bool)
+                -> MXResult<()> {
+    let mut head_handles: Vec<_> = heads.iter().map(|arr| arr.handle).collect();
+    let mut ograd_handles = match head_grads {
+        Some(head_grads) => {
+            if head_grads.len() != heads.len() {
+                return Err(MXError::new("autograd heads and head_grads size mismatch"));
+            }
+            for (head, grad) in heads.iter().zip(head_grads) {
+                try!(head.check_same_shape(grad));
+            }
+            head_grads.iter().map(|arr| arr.handle).collect()
+        }
+        None => vec![ptr::null_mut(); heads.len()],
+    };
+    c_try!(MXAutogradBackwardEx(head_handles.len() as mx_uint,
+                                head_handles.as_mut_ptr(),
+                                ograd_handles.as_mut_ptr(),
+                                0,
+                                ptr::null_mut(),
+                                retain_graph as c_int,
+                                0,
+                                train_mode as c_int,
+                                ptr::null_mut(),
+                                ptr::null_mut()));
+    Ok(())
+}
+
+/// A differentiable function with a gradient implemented in Rust, e.g. to
+/// compute the gradient in a numerically stable way.
+pub trait Function: Send + 'static {
+    /// Compute the outputs from the inputs. Operations in `forward` are not
+    /// recorded, so it should save whatever `backward` needs in `self`.
+    fn forward(&mut self, inputs: &[&NDArray]) -> MXResult<Vec<NDArray>>;
+
+    /// Compute the gradients of the inputs from those of the outputs.
+    fn backward(&mut self, out_grads: &[NDArray]) -> MXResult<Vec<NDArray>>;
+}
+
+// The backward callback signature of the custom function C API.
+type BwdFunc = unsafe extern "C" fn(c_int,
+                                    c_int,
+                                    *mut *mut c_void,
+                                    *const c_int,
+                                    c_int,
+                                    *mut c_void)
+                                    -> c_int;
+
+/// Apply `function` to `inputs`. While recording, `backward` uses
+/// `Function::backward` for the gradients of the inputs.
+pub fn apply<F: Function>(mut function: F, inputs: &[&NDArray]) -> MXResult<Vec<NDArray>> {
+    let outputs = {
+        let _scope = try!(Scope::new(Some(false), None));
+        try!(function.forward(inputs))
+    };
+    if outputs.is_empty() {
+        return Err(MXError::new("autograd function has no outputs"));
+    }
+    if !try!(is_recording()) {
+        return Ok(outputs);
+    }
+    let mut input_handles: Vec<_> = inputs.iter().map(|arr| arr.handle).collect();
+    let mut output_handles: Vec<_> = outputs.iter().map(|arr| arr.handle).collect();
+    let function: Box<dyn Function> = Box::new(function);
+    // mxnet owns the function from here on, even if recording fails.
+    let callbacks = unsafe {
+        let backward =
+            mem::transmute::<BwdFunc, unsafe extern "C" fn() -> c_int>(function_backward);
+        custom::export(function,
+                       vec![Some(backward), custom::delete_callback::<Box<dyn Function>>()])
+    };
+    c_try!(MXCustomFunctionRecord(input_handles.len() as c_int,
+                                  input_handles.as_mut_ptr(),
+                                  output_handles.len() as c_int,
+                                  output_handles.as_mut_ptr(),
+                                  callbacks));
+    Ok(outputs)
+}
+
+unsafe extern "C" fn function_backward(num_ograds: c_int,
+                                       num_igrads: c_int,
+                                       ptrs: *mut *mut c_void,
+                                       reqs: *const c_int,
+                                       _is_train: c_int,
+                                       context: *mut c_void)
+                                       -> c_int {
+    custom::guard("autograd function backward", || {
+        let function = custom::state::<Box<dyn Function>>(context);
+        // Take ownership of the array handles passed by mxnet.
+        let mut arrays: Vec<_> = custom::raw_slice(ptrs, (num_ograds + num_igrads) as usize)
+            .iter()
+            .map(|handle| NDArray { handle: *handle })
+            .collect();
+        let mut in_grads = arrays.split_off(num_ograds as usize);
+        let reqs = try!(custom::op_reqs(reqs, in_grads.len()));
+        let grads = try!(function.backward(&arrays));
+        if grads.len() != in_grads.len() {
+            return Err(MXError::new(format!("autograd function returned {} gradients for {} \
+                                             inputs",
+                                            grads.len(),
+                                            in_grads.len())));
+        }
+        for ((in_grad, req), grad) in in_grads.iter_mut().zip(reqs).zip(&grads) {
+            try!(custom::assign(in_grad, req, grad));
+        }
+        Ok(())
+    })
+}
+
+impl NDArray {
+    /// Allocate a zero gradient array for this array and mark it as a
+    /// variable for `backward`.
+    pub fn attach_grad(&self, grad_req: GradReq) -> MXResult<()> {
+        let grad = try!(self.zeros_like());
+        mark_variables(&[self], &[&grad], &[grad_req])
+    }
+
+    /// The gradient array attached with `attach_grad`, if any.
+    pub fn grad(&self) -> MXResult<Option<NDArray>> {
+        let mut handle = ptr::null_mut();
+        c_try!(MXNDArrayGetGrad(self.handle, &mut handle));
+        if handle.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(NDArray { handle: handle }))
+        }
+    }
+
+    /// An array sharing the data of this one but detached from the recorded
+    /// graph, so that no gradients flow through it.
+    pub fn detach(&self) -> MXResult<NDArray> {
+        let mut handle = ptr::null_mut();
+        c_try!(MXNDArrayDetach(self.handle, &mut handle));
+        Ok(NDArray { handle: handle })
+    }
+
+    /// Compute the gradients of this array with respect to the marked
+    /// variables. See `autograd::backward`.
+    pub fn backward(&self,
+                    head_grad: Option<&NDArray>,
+                    retain_graph: bool,
+                    train_mode: bool)
+                    -> MXResult<()> {
+        match head_grad {
+            Some(head_grad) => backward(&[self], Some(&[head_grad]), retain_graph, train_mode),
+            None => backward(&[self], None, retain_graph, train_mode),
+        }
+    }
+}
@@ -0,0 +1,625 @@
+//! Operators implemented in Rust.
+//!
+//! A custom operator is described by a `CustomOpProp`, which is created from
+//! the operator parameters by the function passed to `register` and creates
+//! the `CustomOp` doing the computation. Registered operators are invoked as
+//! the `Custom` operator with an `op_type` parameter:
+//!
+//! ```ignore
+//! try!(custom::register("softplus", |_| Ok(Box::new(SoftplusProp) as Box<dyn CustomOpProp>)));
+//! let y = try!(Operator::custom("softplus").add_input(&x).invoke());
+//! let sym = try!(SymbolBuilder::custom("softplus").add_input("data", &data).create("sp"));
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+use libc::{c_char, c_int, c_void};
+use mxnet_sys::*;
+
+use ndarray::{Context, DType, NDArray};
+use operator::Operator;
+use symbol::SymbolBuilder;
+use util::*;
+
+/// How an operator writes one of its outputs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OpReq {
+    /// The output is not needed.
+    Null = 0,
+    /// Overwrite the output.
+    Write = 1,
+    /// Overwrite the output, which shares memory with an input.
+    WriteInplace = 2,
+    /// Add to the output.
+    Add = 3,
+}
+
+impl OpReq {
+    pub(crate) fn from_raw(req: c_int) -> MXResult<Self> {
+        match req {
+            0 => Ok(OpReq::Null),
+            1 => Ok(OpReq::Write),
+            2 => Ok(OpReq::WriteInplace),
+            3 => Ok(OpReq::Add),
+            _ => Err(MXError::new("unknown operator request type")),
+        }
+    }
+}
+
+/// Write `src` into the output `dst` as requested by `req`.
+pub fn assign(dst: &mut NDArray, req: OpReq, src: &NDArray) -> MXResult<()> {
+    match req {
+        OpReq::Null => Ok(()),
+        OpReq::Write | OpReq::WriteInplace => src.copy_to(dst),
+        OpReq::Add => {
+            try!(dst.check_same_shape(src));
+            Operator::new("_plus")
+                .add_input_handle(dst.handle)
+                .add_input(src)
+                .invoke_handle_into(dst.handle)
+        }
+    }
+}
+
+/// The computation of a custom operator on a particular context.
+///
+/// mxnet calls operators from its own worker threads.
+pub trait CustomOp: Send {
+    /// Compute `out_data` from `in_data`, writing each output with `assign`
+    /// as requested by `req`.
+    fn forward(&mut self,
+               is_train: bool,
+               req: &[OpReq],
+               in_data: &[NDArray],
+               out_data: &mut [NDArray],
+               aux: &mut [NDArray])
+               -> MXResult<()>;
+
+    /// Compute `in_grad` from the gradients of the outputs, writing each
+    /// gradient with `assign` as requested by `req`.
+    fn backward(&mut self,
+                req: &[OpReq],
+                out_grad: &[NDArray],
+                in_data: &[NDArray],
+                out_data: &[NDArray],
+                in_grad: &mut [NDArray],
+                aux: &mut [NDArray])
+                -> MXResult<()>;
+}
+
+/// The shapes of the arguments, outputs and auxiliary states of an operator.
+pub type InferredShapes = (Vec<Vec<usize>>, Vec<Vec<usize>>, Vec<Vec<usize>>);
+
+/// The properties of a custom operator, created for each use of the operator
+/// from its parameters.
+pub trait CustomOpProp: Send {
+    fn list_arguments(&self) -> Vec<String> {
+        vec!["data".to_string()]
+    }
+
+    fn list_outputs(&self) -> Vec<String> {
+        vec!["output".to_string()]
+    }
+
+    fn list_auxiliary_states(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Infer the shapes of the arguments, outputs and auxiliary states from
+    /// the shapes of the arguments. By default all have the shape of the first
+    /// argument.
+    fn infer_shape(&self, in_shapes: &[Vec<usize>]) -> MXResult<InferredShapes> {
+        let shape = try!(in_shapes.first()
+            .ok_or_else(|| MXError::new("custom operator has no arguments to infer shapes from")));
+        Ok((in_shapes.to_vec(),
+            vec![shape.clone(); self.list_outputs().len()],
+            vec![shape.clone(); self.list_auxiliary_states().len()]))
+    }
+
+    /// Infer the data types of the arguments, outputs and auxiliary states
+    /// from the types of the arguments. By default all have the type of the
+    /// first argument.
+    fn infer_type(&self, in_types: &[DType]) -> MXResult<(Vec<DType>, Vec<DType>, Vec<DType>)> {
+        let dtype = *try!(in_types.first()
+            .ok_or_else(|| MXError::new("custom operator has no arguments to infer types from")));
+        Ok((in_types.to_vec(),
+            vec![dtype; self.list_outputs().len()],
+            vec![dtype; self.list_auxiliary_states().len()]))
+    }
+
+    /// Select the ids of the arrays `backward` needs out of those of the
+    /// output gradients, inputs and outputs. By default it needs all of them.
+    fn declare_backward_dependency(&self,
+                                   out_grad: &[i32],
+                                   in_data: &[i32],
+                                   out_data: &[i32])
+                                   -> Vec<i32> {
+        out_grad.iter().chain(in_data).chain(out_data).cloned().collect()
+    }
+
+    /// Create the operator for inputs of the given shapes and types, the
+    /// arguments followed by the auxiliary states.
+    ///
+    /// mxnet only passes the device type, so `context` has device id 0; use
+    /// the context of the arrays in `CustomOp` for the device id.
+    fn create_operator(&self,
+                       context: Context,
+                       in_shapes: &[Vec<usize>],
+                       in_types: &[DType])
+                       -> MXResult<Box<dyn CustomOp>>;
+}
+
+type PropCreator = dyn Fn(&HashMap<String, String>) -> MXResult<Box<dyn CustomOpProp>>
+                         + Send + Sync;
+
+lazy_static! {
+    static ref PROP_CREATORS: Mutex<HashMap<String, Arc<PropCreator>>> = Mutex::new(HashMap::new());
+}
+
+/// Register a custom operator as `op_type`. `creator` creates its properties
+/// from the parameters of each use of the operator.
+pub fn register<F>(op_type: &str, creator: F) -> MXResult<()>
+    where F: Fn(&HashMap<String, String>) -> MXResult<Box<dyn CustomOpProp>> + Send + Sync + 'static
+{
+    let c_op_type = CString::new(op_type).unwrap();
+    {
+        let mut creators = PROP_CREATORS.lock().unwrap();
+        if creators.contains_key(op_type) {
+            return Err(MXError::new(format!("custom operator {} is already registered", op_type)));
+        }
+        creators.insert(op_type.to_string(), Arc::new(creator));
+    }
+    if unsafe { MXCustomOpRegister(c_op_type.as_ptr(), Some(create_prop)) } != 0 {
+        PROP_CREATORS.lock().unwrap().remove(op_type);
+        return error_result();
+    }
+    Ok(())
+}
+
+impl<'a> Operator<'a> {
+    /// Start an invocation of the custom operator registered as `op_type`.
+    pub fn custom(op_type: &str) -> Self {
+        let mut op = Operator::new("Custom");
+        op.add_param("op_type", op_type);
+        op
+    }
+}
+
+impl<'a> SymbolBuilder<'a> {
+    /// Start building a symbol of the custom operator registered as
+    /// `op_type`.
+    pub fn custom(op_type: &str) -> Self {
+        let mut builder = SymbolBuilder::new("Custom");
+        builder.add_param("op_type", op_type);
+        builder
+    }
+}
+
+// The callback signatures of the custom operator C API.
+pub(crate) type Callback = Option<unsafe extern "C" fn() -> c_int>;
+type DelFunc = unsafe extern "C" fn(*mut c_void) -> c_int;
+type ListFunc = unsafe extern "C" fn(*mut *mut *mut c_char, *mut c_void) -> c_int;
+type InferShapeFunc = unsafe extern "C" fn(c_int, *mut c_int, *mut *mut mx_uint, *mut c_void)
+                                           -> c_int;
+type InferTypeFunc = unsafe extern "C" fn(c_int, *mut c_int, *mut c_void) -> c_int;
+type BwdDepFunc = unsafe extern "C" fn(*const c_int,
+                                       *const c_int,
+                                       *const c_int,
+                                       *mut c_int,
+                                       *mut *mut c_int,
+                                       *mut c_void)
+                                       -> c_int;
+type CreateFunc = unsafe extern "C" fn(*const c_char,
+                                       c_int,
+                                       *mut *mut mx_uint,
+                                       *const c_int,
+                                       *const c_int,
+                                       *mut MXCallbackList,
+                                       *mut c_void)
+                                       -> c_int;
+type FBFunc = unsafe extern "C" fn(c_int,
+                                   *mut *mut c_void,
+                                   *mut c_int,
+                                   *const c_int,
+                                   c_int,
+                                   *mut c_void)
+                                   -> c_int;
+
+macro_rules! callback {
+    ( $func:expr, $ty:ty ) => {
+        Some(unsafe { mem::transmute::<$ty, unsafe extern "C" fn() -> c_int>($func) })
+    };
+}
+
+/// Run a callback from mxnet, which expects one on success and zero on
+/// failure. Errors are passed on as the mxnet last error, and panics must not
+/// unwind into mxnet.
+pub(crate) fn guard<F: FnOnce() -> MXResult<()>>(callback: &str, func: F) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(Ok(())) => 1,
+        Ok(Err(err)) => {
+            set_last_error(&format!("{} failed: {}", callback, err));
+            0
+        }
+        Err(_) => {
+            set_last_error(&format!("{} panicked", callback));
+            0
+        }
+    }
+}
+
+/// A slice from a pointer and length passed by mxnet, which may pass a null
+/// pointer for no elements.
+pub(crate) unsafe fn raw_slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// State handed to mxnet together with its callbacks, which are all called
+/// with the state as context. mxnet owns the state until it calls `delete`.
+pub(crate) struct Exported<S> {
+    state: S,
+    callbacks: Vec<Callback>,
+    contexts: Vec<*mut c_void>,
+    list: MXCallbackList,
+}
+
+/// Export `state`, returning the callback list to hand to mxnet, which stays
+/// valid as long as the state.
+pub(crate) unsafe fn export<S>(state: S, callbacks: Vec<Callback>) -> *mut MXCallbackList {
+    let exported = Box::into_raw(Box::new(Exported {
+        state: state,
+        callbacks: callbacks,
+        contexts: Vec::new(),
+        list: MXCallbackList {
+            num_callbacks: 0,
+            callbacks: ptr::null_mut(),
+            contexts: ptr::null_mut(),
+        },
+    }));
+    let exported_ref = &mut *exported;
+    exported_ref.contexts = vec![exported as *mut c_void; exported_ref.callbacks.len()];
+    exported_ref.list = MXCallbackList {
+        num_callbacks: exported_ref.callbacks.len() as c_int,
+        callbacks: exported_ref.callbacks.as_mut_ptr(),
+        contexts: exported_ref.contexts.as_mut_ptr(),
+    };
+    &mut exported_ref.list
+}
+
+pub(crate) unsafe fn state<'a, S>(context: *mut c_void) -> &'a mut S {
+    &mut (*(context as *mut Exported<S>)).state
+}
+
+unsafe extern "C" fn delete<S>(context: *mut c_void) -> c_int {
+    guard("custom state deletion", || {
+        drop(Box::from_raw(context as *mut Exported<S>));
+        Ok(())
+    })
+}
+
+/// The callback deleting an exported `S`.
+pub(crate) fn delete_callback<S>() -> Callback {
+    callback!(delete::<S>, DelFunc)
+}
+
+/// A null-terminated array of names handed to mxnet.
+struct NameList {
+    names: Vec<CString>,
+    ptrs: Vec<*mut c_char>,
+}
+
+impl NameList {
+    fn new(names: Vec<String>) -> MXResult<Self> {
+        let mut c_names = Vec::with_capacity(names.len());
+        for name in names {
+            c_names.push(try!(CString::new(name)
+                .map_err(|_| MXError::new("custom operator names must not contain nul bytes"))));
+        }
+        let ptrs = c_names.iter()
+            .map(|name| name.as_ptr() as *mut c_char)
+            .chain(Some(ptr::null_mut()))
+            .collect();
+        Ok(NameList {
+            names: c_names,
+            ptrs: ptrs,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+}
+
+struct PropState {
+    prop: Box<dyn CustomOpProp>,
+    arguments: NameList,
+    outputs: NameList,
+    auxiliary_states: NameList,
+    // Inferred shapes and backward dependencies are read by mxnet after the
+    // callbacks return, so they are kept until the next call.
+    shapes: Vec<Vec<mx_uint>>,
+    dependencies: Vec<c_int>,
+}
+
+impl PropState {
+    fn num_tensors(&self) -> usize {
+        self.arguments.len() + self.outputs.len() + self.auxiliary_states.len()
+    }
+
+    fn check_inferred<T>(&self, inferred: &(Vec<T>, Vec<T>, Vec<T>)) -> MXResult<()> {
+        if inferred.0.len() != self.arguments.len() || inferred.1.len() != self.outputs.len() ||
+           inferred.2.len() != self.auxiliary_states.len() {
+            return Err(MXError::new("custom operator inferred the wrong number of arrays"));
+        }
+        Ok(())
+    }
+}
+
+unsafe extern "C" fn create_prop(op_type: *const c_char,
+                                 num_kwargs: c_int,
+                                 keys: *mut *const c_char,
+                                 values: *mut *const c_char,
+                                 ret: *mut MXCallbackList)
+                                 -> c_int {
+    guard("custom operator creation", || {
+        let op_type = CStr::from_ptr(op_type).to_string_lossy().into_owned();
+        let creator = match PROP_CREATORS.lock().unwrap().get(&op_type) {
+            Some(creator) => creator.clone(),
+            None => {
+                return Err(MXError::new(format!("custom operator {} is not registered", op_type)))
+            }
+        };
+        let keys = raw_slice(keys, num_kwargs as usize);
+        let values = raw_slice(values, num_kwargs as usize);
+        let kwargs = keys.iter()
+            .zip(values)
+            .map(|(key, value)| {
+                (CStr::from_ptr(*key).to_string_lossy().into_owned(),
+                 CStr::from_ptr(*value).to_string_lossy().into_owned())
+            })
+            .collect();
+        let prop = try!(creator(&kwargs));
+        let state = PropState {
+            arguments: try!(NameList::new(prop.list_arguments())),
+            outputs: try!(NameList::new(prop.list_outputs())),
+            auxiliary_states: try!(NameList::new(prop.list_auxiliary_states())),
+            prop: prop,
+            shapes: Vec::new(),
+            dependencies: Vec::new(),
+        };
+        // The order of the callbacks is fixed by the C API.
+        *ret = *export(state,
+                       vec![delete_callback::<PropState>(),
+                            callback!(list_arguments, ListFunc),
+                            callback!(list_outputs, ListFunc),
+                            callback!(list_auxiliary_states, ListFunc),
+                            callback!(infer_shape, InferShapeFunc),
+                            callback!(declare_backward_dependency, BwdDepFunc),
+                            callback!(create_operator, CreateFunc),
+                            callback!(infer_type, InferTypeFunc)]);
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn list_arguments(args: *mut *mut *mut c_char, context: *mut c_void) -> c_int {
+    guard("custom operator list_arguments", || {
+        *args = state::<PropState>(context).arguments.ptrs.as_mut_ptr();
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn list_outputs(args: *mut *mut *mut c_char, context: *mut c_void) -> c_int {
+    guard("custom operator list_outputs", || {
+        *args = state::<PropState>(context).outputs.ptrs.as_mut_ptr();
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn list_auxiliary_states(args: *mut *mut *mut c_char,
+                                           context: *mut c_void)
+                                           -> c_int {
+    guard("custom operator list_auxiliary_states", || {
+        *args = state::<PropState>(context).auxiliary_states.ptrs.as_mut_ptr();
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn infer_shape(num_tensors: c_int,
+                                 ndims: *mut c_int,
+                                 shapes: *mut *mut mx_uint,
+                                 context: *mut c_void)
+                                 -> c_int {
+    guard("custom operator infer_shape", || {
+        let state = state::<PropState>(context);
+        if num_tensors as usize != state.num_tensors() {
+            return Err(MXError::new("custom operator shape inference size mismatch"));
+        }
+        let ndims = slice::from_raw_parts_mut(ndims, num_tensors as usize);
+        let shapes = slice::from_raw_parts_mut(shapes, num_tensors as usize);
+        let mut in_shapes = Vec::with_capacity(state.arguments.len());
+        for i in 0..state.arguments.len() {
+            // Leave all shapes unknown until the argument shapes are known.
+            if ndims[i] == 0 {
+                return Ok(());
+            }
+            let shape = raw_slice(shapes[i], ndims[i] as usize);
+            in_shapes.push(shape.iter().map(|dim| *dim as usize).collect());
+        }
+        let inferred = try!(state.prop.infer_shape(&in_shapes));
+        try!(state.check_inferred(&inferred));
+        let (in_shapes, out_shapes, aux_shapes) = inferred;
+        state.shapes = in_shapes.iter()
+            .chain(&out_shapes)
+            .chain(&aux_shapes)
+            .map(|shape| shape.iter().map(|dim| *dim as mx_uint).collect())
+            .collect();
+        for (i, shape) in state.shapes.iter_mut().enumerate() {
+            ndims[i] = shape.len() as c_int;
+            shapes[i] = shape.as_mut_ptr();
+        }
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn infer_type(num_tensors: c_int,
+                                types: *mut c_int,
+                                context: *mut c_void)
+                                -> c_int {
+    guard("custom operator infer_type", || {
+        let state = state::<PropState>(context);
+        if num_tensors as usize != state.num_tensors() {
+            return Err(MXError::new("custom operator type inference size mismatch"));
+        }
+        let types = slice::from_raw_parts_mut(types, num_tensors as usize);
+        let mut in_types = Vec::with_capacity(state.arguments.len());
+        for type_flag in &types[..state.arguments.len()] {
+            // Leave all types unknown until the argument types are known.
+            if *type_flag < 0 {
+                return Ok(());
+            }
+            in_types.push(try!(DType::from_raw(*type_flag)));
+        }
+        let inferred = try!(state.prop.infer_type(&in_types));
+        try!(state.check_inferred(&inferred));
+        let (in_types, out_types, aux_types) = inferred;
+        for (i, dtype) in in_types.iter().chain(&out_types).chain(&aux_types).enumerate() {
+            types[i] = *dtype as c_int;
+        }
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn declare_backward_dependency(out_grad: *const c_int,
+                                                 in_data: *const c_int,
+                                                 out_data: *const c_int,
+                                                 num_deps: *mut c_int,
+                                                 deps: *mut *mut c_int,
+                                                 context: *mut c_void)
+                                                 -> c_int {
+    guard("custom operator declare_backward_dependency", || {
+        let state = state::<PropState>(context);
+        let num_outputs = state.outputs.len();
+        state.dependencies =
+            state.prop.declare_backward_dependency(raw_slice(out_grad, num_outputs),
+                                                   raw_slice(in_data, state.arguments.len()),
+                                                   raw_slice(out_data, num_outputs));
+        *num_deps = state.dependencies.len() as c_int;
+        *deps = state.dependencies.as_mut_ptr();
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn create_operator(ctx: *const c_char,
+                                     num_inputs: c_int,
+                                     shapes: *mut *mut mx_uint,
+                                     ndims: *const c_int,
+                                     dtypes: *const c_int,
+                                     ret: *mut MXCallbackList,
+                                     context: *mut c_void)
+                                     -> c_int {
+    guard("custom operator create_operator", || {
+        let state = state::<PropState>(context);
+        let ctx = try!(CStr::from_ptr(ctx).to_string_lossy().parse());
+        let shapes = raw_slice(shapes, num_inputs as usize);
+        let ndims = raw_slice(ndims, num_inputs as usize);
+        let mut in_shapes = Vec::with_capacity(num_inputs as usize);
+        for (shape, ndim) in shapes.iter().zip(ndims) {
+            let shape = raw_slice(*shape, *ndim as usize);
+            in_shapes.push(shape.iter().map(|dim| *dim as usize).collect());
+        }
+        let mut in_types = Vec::with_capacity(num_inputs as usize);
+        for type_flag in raw_slice(dtypes, num_inputs as usize) {
+            in_types.push(try!(DType::from_raw(*type_flag)));
+        }
+        let op = try!(state.prop.create_operator(ctx, &in_shapes, &in_types));
+        *ret = *export(op,
+                       vec![delete_callback::<Box<dyn CustomOp>>(),
+                            callback!(forward, FBFunc),
+                            callback!(backward, FBFunc)]);
+        Ok(())
+    })
+}
+
+/// The arrays passed to `forward` and `backward`, sorted by their tags.
+#[derive(Default)]
+struct Tensors {
+    in_data: Vec<NDArray>,
+    out_data: Vec<NDArray>,
+    in_grad: Vec<NDArray>,
+    out_grad: Vec<NDArray>,
+    aux: Vec<NDArray>,
+}
+
+impl Tensors {
+    /// Take ownership of the array handles passed by mxnet.
+    unsafe fn new(size: c_int, ptrs: *mut *mut c_void, tags: *mut c_int) -> MXResult<Self> {
+        let mut tensors = Tensors::default();
+        let ptrs = raw_slice(ptrs, size as usize);
+        let tags = raw_slice(tags, size as usize);
+        for (handle, tag) in ptrs.iter().zip(tags) {
+            let arr = NDArray { handle: *handle };
+            match *tag {
+                0 => tensors.in_data.push(arr),
+                1 => tensors.out_data.push(arr),
+                2 => tensors.in_grad.push(arr),
+                3 => tensors.out_grad.push(arr),
+                4 => tensors.aux.push(arr),
+                _ => return Err(MXError::new("unknown custom operator array tag")),
+            }
+        }
+        Ok(tensors)
+    }
+}
+
+pub(crate) unsafe fn op_reqs(reqs: *const c_int, len: usize) -> MXResult<Vec<OpReq>> {
+    raw_slice(reqs, len).iter().map(|req| OpReq::from_raw(*req)).collect()
+}
+
+unsafe extern "C" fn forward(size: c_int,
+                             ptrs: *mut *mut c_void,
+                             tags: *mut c_int,
+                             reqs: *const c_int,
+                             is_train: c_int,
+                             context: *mut c_void)
+                             -> c_int {
+    guard("custom operator forward", || {
+        let op = state::<Box<dyn CustomOp>>(context);
+        let mut tensors = try!(Tensors::new(size, ptrs, tags));
+        let reqs = try!(op_reqs(reqs, tensors.out_data.len()));
+        op.forward(is_train != 0,
+                   &reqs,
+                   &tensors.in_data,
+                   &mut tensors.out_data,
+                   &mut tensors.aux)
+    })
+}
+
+unsafe extern "C" fn backward(size: c_int,
+                              ptrs: *mut *mut c_void,
+                              tags: *mut c_int,
+                              reqs: *const c_int,
+                              _is_train: c_int,
+                              context: *mut c_void)
+                              -> c_int {
+    guard("custom operator backward", || {
+        let op = state::<Box<dyn CustomOp>>(context);
+        let mut tensors = try!(Tensors::new(size, ptrs, tags));
+        let reqs = try!(op_reqs(reqs, tensors.in_grad.len()));
+        op.backward(&reqs,
+                    &tensors.out_grad,
+                    &tensors.in_data,
+                    &tensors.out_data,
+                    &mut tensors.in_grad,
+                    &mut tensors.aux)
+    })
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+
+use half::f16;
+
+use ndarray::{DType, NDArray};
+
+/// Options for printing `NDArray`s.
+#[derive(Debug, Copy, Clone)]
+pub struct PrintOptions {
+    /// Digits after the decimal point of floating point elements. Overridden
+    /// by the precision of the format string, e.g. `{:.2}`.
+    pub precision: usize,
+    /// Arrays with more elements than this are summarized.
+    pub threshold: usize,
+    /// Elements kept at the beginning and end of each axis when summarizing.
+    pub edge_items: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            precision: 4,
+            threshold: 1000,
+            edge_items: 3,
+        }
+    }
+}
+
+/// Prints an `NDArray` with custom `PrintOptions`, see `NDArray::display`.
+pub struct NDArrayDisplay<'a> {
+    array: &'a NDArray,
+    options: PrintOptions,
+}
+
+impl NDArray {
+    /// Print the array with custom options:
+    ///
+    /// ```ignore
+    /// println!("{}", arr.display(PrintOptions { threshold: 10, ..Default::default() }));
+    /// ```
+    pub fn display(&self, options: PrintOptions) -> NDArrayDisplay<'_> {
+        NDArrayDisplay {
+            array: self,
+            options: options,
+        }
+    }
+}
+
+/// Renders the elements of a host copy of an array.
+struct Printer {
+    dtype: DType,
+    data: Vec<u8>,
+    shape: Vec<usize>,
+    precision: usize,
+    summarize: bool,
+    edge_items: usize,
+}
+
+impl Printer {
+    fn element(&self, index: usize) -> String {
+        let size = self.dtype.size();
+        let bytes = &self.data[index * size..(index + 1) * size];
+        let bits = bytes.iter().rev().fold(0u64, |acc, x| acc << 8 | *x as u64);
+        match self.dtype {
+            DType::Float32 => format!("{:.*}", self.precision, f32::from_bits(bits as u32)),
+            DType::Float64 => format!("{:.*}", self.precision, f64::from_bits(bits)),
+            DType::Float16 => {
+                format!("{:.*}", self.precision, f16::from_bits(bits as u16).to_f32())
+            }
+            DType::UInt8 => (bits as u8).to_string(),
+            DType::Int32 => (bits as u32 as i32).to_string(),
+            DType::Int8 => (bits as u8 as i8).to_string(),
+            DType::Int64 => (bits as i64).to_string(),
+        }
+    }
+
+    /// The indices shown along an axis of size `dim`, with `None` standing
+    /// for the summarized elements.
+    fn indices(&self, dim: usize) -> Vec<Option<usize>> {
+        if self.summarize && dim > 2 * self.edge_items {
+            (0..self.edge_items)
+                .map(Some)
+                .chain(Some(None))
+                .chain((dim - self.edge_items..dim).map(Some))
+                .collect()
+        } else {
+            (0..dim).map(Some).collect()
+        }
+    }
+
+    /// Format the shown elements of the sub-array at `offset` along `axis`.
+    fn elements(&self, axis: usize, offset: usize, out: &mut Vec<String>) {
+        let stride: usize = self.shape[axis + 1..].iter().product();
+        for index in self.indices(self.shape[axis]) {
+            match index {
+                Some(i) if axis + 1 == self.shape.len() => {
+                    out.push(self.element(offset + i))
+                }
+                Some(i) => self.elements(axis + 1, offset + i * stride, out),
+                None => {}
+            }
+        }
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut elements = Vec::new();
+        self.elements(0, 0, &mut elements);
+        let width = elements.iter().map(|x| x.len()).max().unwrap_or(0);
+        self.write(f, 0, &mut elements.into_iter(), width)
+    }
+
+    fn write(&self,
+             f: &mut fmt::Formatter,
+             axis: usize,
+             elements: &mut dyn Iterator<Item = String>,
+             width: usize)
+             -> fmt::Result {
+        let ndim = self.shape.len();
+        try!(write!(f, "["));
+        let indices = self.indices(self.shape[axis]);
+        for (n, index) in indices.iter().enumerate() {
+            if n > 0 {
+                if axis + 1 == ndim {
+                    try!(write!(f, ", "));
+                } else {
+                    try!(write!(f, ",\n"));
+                    for _ in axis + 2..ndim {
+                        try!(write!(f, "\n"));
+                    }
+                    for _ in 0..axis + 1 {
+                        try!(write!(f, " "));
+                    }
+                }
+            }
+            match *index {
+                Some(_) if axis + 1 == ndim => {
+                    let element = elements.next().unwrap_or_default();
+                    try!(write!(f, "{:>1$}", element, width));
+                }
+                Some(_) => try!(self.write(f, axis + 1, elements, width)),
+                None => try!(write!(f, "...")),
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+fn fmt_ndarray(array: &NDArray, options: PrintOptions, f: &mut fmt::Formatter) -> fmt::Result {
+    let shape = array.shape();
+    let footer: Vec<String> = shape.iter().map(|dim| dim.to_string()).collect();
+    let (dtype, context) = match (array.dtype(), array.context()) {
+        (Ok(dtype), Ok(context)) => (dtype, context),
+        (Err(err), _) | (_, Err(err)) => {
+            return write!(f, "<NDArray {}: {}>", footer.join("x"), err)
+        }
+    };
+    let data = match array.host_bytes() {
+        Ok(data) => data,
+        Err(err) => return write!(f, "<NDArray {} @{}: {}>", footer.join("x"), context, err),
+    };
+
+    if !shape.is_empty() && !data.is_empty() {
+        let printer = Printer {
+            dtype: dtype,
+            data: data,
+            precision: f.precision().unwrap_or(options.precision),
+            summarize: array.size() > options.threshold,
+            edge_items: options.edge_items,
+            shape: shape,
+        };
+        try!(printer.fmt(f));
+    } else {
+        try!(write!(f, "[]"));
+    }
+    write!(f,
+           "\n<NDArray {} {} @{}>",
+           footer.join("x"),
+           dtype.name(),
+           context)
+}
+
+impl<'a> fmt::Display for NDArrayDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_ndarray(self.array, self.options, f)
+    }
+}
+
+/// Prints the elements in the style of numpy, followed by the shape, data type
+/// and context. Large arrays are summarized, see `PrintOptions`.
+impl fmt::Display for NDArray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_ndarray(self, Default::default(), f)
+    }
+}
+
+impl fmt::Debug for NDArray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_ndarray(self, Default::default(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::Printer;
+    use ndarray::DType;
+
+    impl fmt::Display for Printer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            Printer::fmt(self, f)
+        }
+    }
+
+    fn printer(shape: Vec<usize>, summarize: bool) -> Printer {
+        let size: usize = shape.iter().product();
+        Printer {
+            dtype: DType::Int32,
+            data: (0..size as u32).flat_map(|x| vec![x as u8, 0, 0, 0]).collect(),
+            shape: shape,
+            precision: 4,
+            summarize: summarize,
+            edge_items: 1,
+        }
+    }
+
+    #[test]
+    fn print_nested() {
+        assert_eq!(printer(vec![2, 3], false).to_string(), "[[0, 1, 2],\n [3, 4, 5]]");
+        assert_eq!(printer(vec![2, 1, 2], false).to_string(),
+                   "[[[0, 1]],\n\n [[2, 3]]]");
+    }
+
+    #[test]
+    fn print_summarized() {
+        assert_eq!(printer(vec![12], true).to_string(), "[ 0, ..., 11]");
+        assert_eq!(printer(vec![3, 3], true).to_string(), "[[0, ..., 2],\n ...,\n [6, ..., 8]]");
+    }
+
+    #[test]
+    fn print_floats() {
+        let printer = Printer {
+            dtype: DType::Float64,
+            data: (-1.5f64).to_bits().to_le_bytes().to_vec(),
+            shape: vec![1],
+            precision: 2,
+            summarize: false,
+            edge_items: 3,
+        };
+        assert_eq!(printer.to_string(), "[-1.50]");
+    }
+}
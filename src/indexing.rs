@@ -0,0 +1,198 @@
+use operator::{Operator, Param};
+use ndarray::{DType, NDArray};
+use util::*;
+
+/// How out-of-bounds indices are handled by `NDArray::take` and
+/// `NDArray::pick`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndexMode {
+    /// Clamp indices to the valid range.
+    Clip,
+    /// Wrap indices around the axis.
+    Wrap,
+    /// Fail on out-of-bounds indices. Only supported by `take`.
+    Raise,
+}
+
+impl Param for IndexMode {
+    fn to_param(&self) -> String {
+        match *self {
+            IndexMode::Clip => "clip",
+            IndexMode::Wrap => "wrap",
+            IndexMode::Raise => "raise",
+        }
+        .to_string()
+    }
+}
+
+fn check_indices(indices: &NDArray) -> MXResult<()> {
+    let dtype = try!(indices.dtype());
+    if !dtype.is_integer() {
+        return Err(MXError::new(format!("NDArray indices must have an integer data type, not {}",
+                                        dtype.name())));
+    }
+    Ok(())
+}
+
+impl NDArray {
+    /// Select the slices at `indices` along `axis`. The result has the shape
+    /// of `indices` in place of `axis`.
+    pub fn take(&self, indices: &NDArray, axis: usize, mode: IndexMode) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        try!(check_indices(indices));
+        Operator::new("take")
+            .add_input(self)
+            .add_input(indices)
+            .add_param("axis", axis)
+            .add_param("mode", mode)
+            .invoke()
+    }
+
+    /// Select one element along `axis` for each position of the other axes,
+    /// e.g. the score of the label of each row. `index` has the shape of the
+    /// array without `axis`, or with `axis` of size one.
+    pub fn pick(&self,
+                index: &NDArray,
+                axis: usize,
+                keepdims: bool,
+                mode: IndexMode)
+                -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        try!(check_indices(index));
+        if mode == IndexMode::Raise {
+            return Err(MXError::new("NDArray pick does not support raising on invalid indices"));
+        }
+        let mut expected = self.shape();
+        let shape = index.shape();
+        if shape.len() == expected.len() {
+            expected[axis] = 1;
+        } else {
+            expected.remove(axis);
+        }
+        if shape != expected {
+            return Err(MXError::new(format!("cannot pick with index of shape {:?} from NDArray \
+                                             of shape {:?} along axis {}",
+                                            shape,
+                                            self.shape(),
+                                            axis)));
+        }
+        Operator::new("pick")
+            .add_input(self)
+            .add_input(index)
+            .add_param("axis", axis)
+            .add_param("keepdims", keepdims)
+            .add_param("mode", mode)
+            .invoke()
+    }
+
+    /// Encode the indices as one-hot vectors of length `depth` along a new
+    /// last axis.
+    pub fn one_hot(&self,
+                   depth: usize,
+                   on_value: f64,
+                   off_value: f64,
+                   dtype: DType)
+                   -> MXResult<NDArray> {
+        try!(check_indices(self));
+        self.op("one_hot")
+            .add_param("depth", depth)
+            .add_param("on_value", on_value)
+            .add_param("off_value", off_value)
+            .add_param("dtype", dtype)
+            .invoke()
+    }
+
+    /// Take elements from `x` where `condition` is non-zero and from `y`
+    /// elsewhere, like numpy's `where`.
+    ///
+    /// `condition` has the shape of `x` and `y`, or is one-dimensional and
+    /// selects whole slices along the first axis.
+    pub fn where_(condition: &NDArray, x: &NDArray, y: &NDArray) -> MXResult<NDArray> {
+        try!(x.check_same_shape(y));
+        let shape = condition.shape();
+        let x_shape = x.shape();
+        if shape != x_shape && !(shape.len() == 1 && x_shape.first() == Some(&shape[0])) {
+            return Err(MXError::new(format!("cannot select from NDArrays of shape {:?} with \
+                                             condition of shape {:?}",
+                                            x_shape,
+                                            shape)));
+        }
+        Operator::new("where")
+            .add_input(condition)
+            .add_input(x)
+            .add_input(y)
+            .invoke()
+    }
+
+    /// Gather the elements or slices addressed by the first axis of
+    /// `indices`, which has one row for each leading axis being indexed.
+    pub fn gather_nd(&self, indices: &NDArray) -> MXResult<NDArray> {
+        try!(check_indices(indices));
+        let shape = indices.shape();
+        if shape.is_empty() || shape[0] > self.ndim() {
+            return Err(MXError::new(format!("cannot gather with indices of shape {:?} from \
+                                             NDArray with {} dimensions",
+                                            shape,
+                                            self.ndim())));
+        }
+        Operator::new("gather_nd")
+            .add_input(self)
+            .add_input(indices)
+            .invoke()
+    }
+
+    /// Scatter `data` into a new zero array of `shape`, the inverse of
+    /// `gather_nd`. Indices that appear more than once write an arbitrary one
+    /// of their values.
+    pub fn scatter_nd(data: &NDArray, indices: &NDArray, shape: &[usize]) -> MXResult<NDArray> {
+        try!(check_indices(indices));
+        let index_shape = indices.shape();
+        if index_shape.is_empty() || index_shape[0] > shape.len() {
+            return Err(MXError::new(format!("cannot scatter with indices of shape {:?} into \
+                                             shape {:?}",
+                                            index_shape,
+                                            shape)));
+        }
+        let expected: Vec<usize> = index_shape[1..]
+            .iter()
+            .chain(&shape[index_shape[0]..])
+            .cloned()
+            .collect();
+        if data.shape() != expected {
+            return Err(MXError::new(format!("cannot scatter data of shape {:?}, expected {:?}",
+                                            data.shape(),
+                                            expected)));
+        }
+        Operator::new("scatter_nd")
+            .add_input(data)
+            .add_input(indices)
+            .add_param("shape", shape)
+            .invoke()
+    }
+
+    /// Keep the slices along `axis` where the one-dimensional `mask` is
+    /// non-zero.
+    pub fn boolean_mask(&self, mask: &NDArray, axis: usize) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        let shape = mask.shape();
+        if shape.len() != 1 || shape[0] != self.shape()[axis] {
+            return Err(MXError::new(format!("cannot mask axis {} of NDArray of shape {:?} with \
+                                             mask of shape {:?}",
+                                            axis,
+                                            self.shape(),
+                                            shape)));
+        }
+        Operator::new("_contrib_boolean_mask")
+            .add_input(self)
+            .add_input(mask)
+            .add_param("axis", axis)
+            .invoke()
+    }
+
+    /// Replace the elements where `mask` is non-zero with `value`.
+    pub fn masked_fill(&self, mask: &NDArray, value: f64) -> MXResult<NDArray> {
+        let fill = try!(NDArray::full(&self.shape(), value, try!(self.context())));
+        let fill = try!(fill.astype(try!(self.dtype())));
+        NDArray::where_(mask, &fill, self)
+    }
+}
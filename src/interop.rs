@@ -0,0 +1,64 @@
+use nd::{Array, ArrayBase, ArrayD, ArrayViewD, ArrayViewMutD, Data, Dimension, IxDyn};
+
+use ndarray::{Context, Element, NDArray, NDArrayBuilder};
+use util::*;
+
+impl NDArray {
+    /// Copy an `ndarray` array into a new array on `context`, keeping its
+    /// shape and element type.
+    pub fn from_array<T, S, D>(array: &ArrayBase<S, D>, context: Context) -> MXResult<Self>
+        where T: Element,
+              S: Data<Elem = T>,
+              D: Dimension
+    {
+        let shape = array.shape().iter().map(|x| *x as u32).collect();
+        match array.as_slice() {
+            Some(data) => NDArrayBuilder::from(data).shape(shape).context(context).create(),
+            None => {
+                let data: Vec<T> = array.iter().cloned().collect();
+                NDArrayBuilder::from(&data).shape(shape).context(context).create()
+            }
+        }
+    }
+
+    /// Copy the array into a new `ndarray` array of the same shape.
+    pub fn to_array<T: Element>(&self) -> MXResult<ArrayD<T>> {
+        let (shape, data) = try!(self.to_vec_with_shape());
+        match Array::from_shape_vec(IxDyn(&shape), data) {
+            Ok(array) => Ok(array),
+            Err(_) => Err(MXError::new("NDArray shape mismatch")),
+        }
+    }
+
+    /// Borrow a CPU array as an `ndarray` view without copying.
+    ///
+    /// See `as_slice` for the synchronization this implies.
+    ///
+    /// # Safety
+    ///
+    /// The same as for `as_slice`.
+    pub unsafe fn as_array_view<T: Element>(&self) -> MXResult<ArrayViewD<'_, T>> {
+        let shape = self.shape();
+        let data = try!(self.as_slice());
+        match ArrayViewD::from_shape(IxDyn(&shape), data) {
+            Ok(view) => Ok(view),
+            Err(_) => Err(MXError::new("NDArray shape mismatch")),
+        }
+    }
+
+    /// Mutably borrow a CPU array as an `ndarray` view without copying.
+    ///
+    /// See `as_mut_slice` for the synchronization this implies.
+    ///
+    /// # Safety
+    ///
+    /// The same as for `as_mut_slice`.
+    pub unsafe fn as_array_view_mut<T: Element>(&mut self) -> MXResult<ArrayViewMutD<'_, T>> {
+        let shape = self.shape();
+        let data = try!(self.as_mut_slice());
+        match ArrayViewMutD::from_shape(IxDyn(&shape), data) {
+            Ok(view) => Ok(view),
+            Err(_) => Err(MXError::new("NDArray shape mismatch")),
+        }
+    }
+}
@@ -1,15 +1,42 @@
+extern crate half;
 #[macro_use]
 extern crate lazy_static;
 extern crate libc;
 extern crate mxnet_sys;
+#[cfg(feature = "ndarray")]
+extern crate ndarray as nd;
+extern crate zip;
 
 #[macro_use]
 pub mod util;
+pub mod autograd;
+pub mod custom;
+mod display;
+mod indexing;
+#[cfg(feature = "ndarray")]
+mod interop;
+mod linalg;
+mod manip;
+mod math;
 pub mod ndarray;
+mod npy;
+pub mod operator;
+pub mod params;
+pub mod random;
+mod reduce;
+mod sort;
 pub mod symbol;
 
-pub use util::{MXError, random_seed, notify_shutdown};
-pub use ndarray::{Context, NDArray, NDArrayBuilder};
+pub use util::{MXError, random_seed, random_seed_context, notify_shutdown, wait_all};
+pub use autograd::GradReq;
+pub use custom::{CustomOp, CustomOpProp, OpReq};
+pub use display::{NDArrayDisplay, PrintOptions};
+pub use half::f16;
+pub use indexing::IndexMode;
+pub use manip::PadMode;
+pub use ndarray::{Context, DeviceType, DType, Element, Index, NDArray, NDArrayBuilder};
+pub use operator::{Operator, OperatorInfo};
+pub use sort::TopKOutput;
 pub use symbol::{Symbol, SymbolBuilder, Variable, Group};
 
 #[cfg(test)]
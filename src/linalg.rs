@@ -0,0 +1,199 @@
+use ndarray::NDArray;
+use util::*;
+
+/// The batch dimensions, rows and columns of an array holding a matrix in its
+/// last two axes, after an optional transpose.
+fn matrices(arr: &NDArray, transpose: bool) -> MXResult<(Vec<usize>, usize, usize)> {
+    let mut shape = arr.shape();
+    if shape.len() < 2 {
+        return Err(MXError::new(format!("NDArray of shape {:?} is not a matrix", shape)));
+    }
+    let cols = shape.pop().unwrap();
+    let rows = shape.pop().unwrap();
+    if transpose {
+        Ok((shape, cols, rows))
+    } else {
+        Ok((shape, rows, cols))
+    }
+}
+
+fn check_square(arr: &NDArray) -> MXResult<()> {
+    let (_, rows, cols) = try!(matrices(arr, false));
+    if rows != cols {
+        return Err(MXError::new(format!("NDArray of shape {:?} is not square", arr.shape())));
+    }
+    Ok(())
+}
+
+fn check_batches(lhs: &[usize], rhs: &[usize]) -> MXResult<()> {
+    if lhs != rhs {
+        return Err(MXError::new(format!("NDArray batch shape mismatch: {:?} and {:?}", lhs, rhs)));
+    }
+    Ok(())
+}
+
+fn check_inner(lhs: usize, rhs: usize) -> MXResult<()> {
+    if lhs != rhs {
+        return Err(MXError::new(format!("NDArray inner dimension mismatch: {} and {}", lhs, rhs)));
+    }
+    Ok(())
+}
+
+impl NDArray {
+    /// The dot product of two arrays, contracting the last axis of `self` with
+    /// the first axis of `rhs`. The transpose flags reverse the axes of the
+    /// respective operand first.
+    pub fn dot(&self, rhs: &NDArray, transpose_a: bool, transpose_b: bool) -> MXResult<NDArray> {
+        let (lhs_shape, rhs_shape) = (self.shape(), rhs.shape());
+        if lhs_shape.is_empty() || rhs_shape.is_empty() {
+            return Err(MXError::new("NDArray dot of an empty array"));
+        }
+        let lhs_inner = if transpose_a {
+            lhs_shape[0]
+        } else {
+            lhs_shape[lhs_shape.len() - 1]
+        };
+        let rhs_inner = if transpose_b {
+            rhs_shape[rhs_shape.len() - 1]
+        } else {
+            rhs_shape[0]
+        };
+        try!(check_inner(lhs_inner, rhs_inner));
+        self.op("dot")
+            .add_input(rhs)
+            .add_param("transpose_a", transpose_a)
+            .add_param("transpose_b", transpose_b)
+            .invoke()
+    }
+
+    /// Matrix products of two batches of matrices of shapes `(batch, m, k)`
+    /// and `(batch, k, n)`.
+    pub fn batch_dot(&self,
+                     rhs: &NDArray,
+                     transpose_a: bool,
+                     transpose_b: bool)
+                     -> MXResult<NDArray> {
+        if self.ndim() != 3 || rhs.ndim() != 3 {
+            return Err(MXError::new("NDArray batch_dot requires three-dimensional arrays"));
+        }
+        let (lhs_batch, _, lhs_inner) = try!(matrices(self, transpose_a));
+        let (rhs_batch, rhs_inner, _) = try!(matrices(rhs, transpose_b));
+        try!(check_batches(&lhs_batch, &rhs_batch));
+        try!(check_inner(lhs_inner, rhs_inner));
+        self.op("batch_dot")
+            .add_input(rhs)
+            .add_param("transpose_a", transpose_a)
+            .add_param("transpose_b", transpose_b)
+            .invoke()
+    }
+
+    /// Permute the axes of the array so that axis `i` of the result is axis
+    /// `axes[i]` of `self`. Reverses the axes when `axes` is empty.
+    pub fn transpose(&self, axes: &[usize]) -> MXResult<NDArray> {
+        let mut op = self.op("transpose");
+        if !axes.is_empty() {
+            let mut sorted = axes.to_vec();
+            sorted.sort();
+            if sorted != (0..self.ndim()).collect::<Vec<_>>() {
+                return Err(MXError::new(format!("{:?} is not a permutation of the axes of \
+                                                 NDArray of shape {:?}",
+                                                axes,
+                                                self.shape())));
+            }
+            op.add_param("axes", axes);
+        }
+        op.invoke()
+    }
+
+    /// `alpha * op(self) * op(b) + beta * c` for batches of matrices in the
+    /// last two axes, where `op` optionally transposes its operand.
+    pub fn linalg_gemm(&self,
+                       b: &NDArray,
+                       c: &NDArray,
+                       transpose_a: bool,
+                       transpose_b: bool,
+                       alpha: f64,
+                       beta: f64)
+                       -> MXResult<NDArray> {
+        let (a_batch, a_rows, a_inner) = try!(matrices(self, transpose_a));
+        let (b_batch, b_inner, b_cols) = try!(matrices(b, transpose_b));
+        let (c_batch, c_rows, c_cols) = try!(matrices(c, false));
+        try!(check_batches(&a_batch, &b_batch));
+        try!(check_batches(&a_batch, &c_batch));
+        try!(check_inner(a_inner, b_inner));
+        if (a_rows, b_cols) != (c_rows, c_cols) {
+            return Err(MXError::new(format!("NDArray gemm output shape mismatch: {:?}",
+                                            c.shape())));
+        }
+        self.op("_linalg_gemm")
+            .add_input(b)
+            .add_input(c)
+            .add_param("transpose_a", transpose_a)
+            .add_param("transpose_b", transpose_b)
+            .add_param("alpha", alpha)
+            .add_param("beta", beta)
+            .invoke()
+    }
+
+    /// `alpha * op(self) * op(b)` for batches of matrices in the last two
+    /// axes, where `op` optionally transposes its operand.
+    pub fn linalg_gemm2(&self,
+                        b: &NDArray,
+                        transpose_a: bool,
+                        transpose_b: bool,
+                        alpha: f64)
+                        -> MXResult<NDArray> {
+        let (a_batch, _, a_inner) = try!(matrices(self, transpose_a));
+        let (b_batch, b_inner, _) = try!(matrices(b, transpose_b));
+        try!(check_batches(&a_batch, &b_batch));
+        try!(check_inner(a_inner, b_inner));
+        self.op("_linalg_gemm2")
+            .add_input(b)
+            .add_param("transpose_a", transpose_a)
+            .add_param("transpose_b", transpose_b)
+            .add_param("alpha", alpha)
+            .invoke()
+    }
+
+    /// The lower triangular Cholesky factors of a batch of symmetric positive
+    /// definite matrices.
+    pub fn linalg_potrf(&self) -> MXResult<NDArray> {
+        try!(check_square(self));
+        self.op("_linalg_potrf").invoke()
+    }
+
+    /// The inverses of matrices given their lower triangular Cholesky factors,
+    /// as computed by `linalg_potrf`.
+    pub fn linalg_potri(&self) -> MXResult<NDArray> {
+        try!(check_square(self));
+        self.op("_linalg_potri").invoke()
+    }
+
+    /// Solves `op(self) * x = alpha * b`, or `x * op(self) = alpha * b` if
+    /// `rightside` is set, for lower triangular matrices `self`, where `op`
+    /// optionally transposes its operand.
+    pub fn linalg_trsm(&self,
+                       b: &NDArray,
+                       transpose: bool,
+                       rightside: bool,
+                       alpha: f64)
+                       -> MXResult<NDArray> {
+        try!(check_square(self));
+        let (a_batch, n, _) = try!(matrices(self, false));
+        let (b_batch, b_rows, b_cols) = try!(matrices(b, false));
+        try!(check_batches(&a_batch, &b_batch));
+        try!(check_inner(n, if rightside { b_cols } else { b_rows }));
+        self.op("_linalg_trsm")
+            .add_input(b)
+            .add_param("transpose", transpose)
+            .add_param("rightside", rightside)
+            .add_param("alpha", alpha)
+            .invoke()
+    }
+
+    /// The inverses of a batch of square matrices.
+    pub fn linalg_inverse(&self) -> MXResult<NDArray> {
+        try!(check_square(self));
+        self.op("_linalg_inverse").invoke()
+    }
+}
@@ -0,0 +1,189 @@
+use operator::{Operator, Param};
+use ndarray::NDArray;
+use util::*;
+
+/// How `NDArray::pad` fills the padded elements.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PadMode {
+    /// Pad with a constant value.
+    Constant(f64),
+    /// Repeat the edge elements.
+    Edge,
+    /// Mirror the elements next to the edge.
+    Reflect,
+}
+
+impl Param for PadMode {
+    fn to_param(&self) -> String {
+        match *self {
+            PadMode::Constant(_) => "constant",
+            PadMode::Edge => "edge",
+            PadMode::Reflect => "reflect",
+        }
+        .to_string()
+    }
+}
+
+fn check_not_empty(arrays: &[&NDArray]) -> MXResult<()> {
+    if arrays.is_empty() {
+        return Err(MXError::new("no NDArrays to join"));
+    }
+    Ok(())
+}
+
+impl NDArray {
+    /// Join arrays along an existing axis. The other axes must match.
+    pub fn concat(arrays: &[&NDArray], axis: usize) -> MXResult<NDArray> {
+        try!(check_not_empty(arrays));
+        try!(arrays[0].check_axis(axis));
+        let mut expected = arrays[0].shape();
+        for arr in arrays {
+            let shape = arr.shape();
+            if shape.len() == expected.len() {
+                expected[axis] = shape[axis];
+            }
+            if shape != expected {
+                return Err(MXError::new(format!("cannot concatenate NDArrays of shapes {:?} \
+                                                 and {:?} along axis {}",
+                                                arrays[0].shape(),
+                                                arr.shape(),
+                                                axis)));
+            }
+        }
+        Operator::new("Concat")
+            .add_inputs(arrays)
+            .add_param("num_args", arrays.len())
+            .add_param("dim", axis)
+            .invoke()
+    }
+
+    /// Join arrays of the same shape along a new axis.
+    pub fn stack(arrays: &[&NDArray], axis: usize) -> MXResult<NDArray> {
+        try!(check_not_empty(arrays));
+        let shape = arrays[0].shape();
+        if axis > shape.len() {
+            return Err(MXError::new(format!("axis {} out of bounds for stacking NDArrays with \
+                                             {} dimensions",
+                                            axis,
+                                            shape.len())));
+        }
+        for arr in arrays {
+            try!(arrays[0].check_same_shape(arr));
+        }
+        Operator::new("stack")
+            .add_inputs(arrays)
+            .add_param("num_args", arrays.len())
+            .add_param("axis", axis)
+            .invoke()
+    }
+
+    /// Split the array into `num_outputs` equal parts along `axis`, removing
+    /// the axis from the parts if `squeeze_axis` is set.
+    pub fn split(&self,
+                 num_outputs: usize,
+                 axis: usize,
+                 squeeze_axis: bool)
+                 -> MXResult<Vec<NDArray>> {
+        try!(self.check_axis(axis));
+        let dim = self.shape()[axis];
+        if num_outputs == 0 || dim % num_outputs != 0 {
+            return Err(MXError::new(format!("cannot split axis of size {} into {} parts",
+                                            dim,
+                                            num_outputs)));
+        }
+        if squeeze_axis && dim != num_outputs {
+            return Err(MXError::new("squeezed NDArray split parts must have size one"));
+        }
+        self.op("split")
+            .add_param("num_outputs", num_outputs)
+            .add_param("axis", axis)
+            .add_param("squeeze_axis", squeeze_axis)
+            .invoke_all()
+    }
+
+    /// Repeat the whole array `reps[i]` times along axis `i`.
+    pub fn tile(&self, reps: &[usize]) -> MXResult<NDArray> {
+        if reps.is_empty() {
+            return Err(MXError::new("NDArray tile requires repetitions"));
+        }
+        self.op("tile").add_param("reps", reps).invoke()
+    }
+
+    /// Repeat each element `repeats` times along `axis`, or in the flattened
+    /// array if `axis` is `None`.
+    pub fn repeat(&self, repeats: usize, axis: Option<usize>) -> MXResult<NDArray> {
+        if let Some(axis) = axis {
+            try!(self.check_axis(axis));
+        }
+        self.op("repeat")
+            .add_param("repeats", repeats)
+            .add_param("axis", axis)
+            .invoke()
+    }
+
+    /// Insert an axis of size one at position `axis`.
+    pub fn expand_dims(&self, axis: usize) -> MXResult<NDArray> {
+        if axis > self.ndim() {
+            return Err(MXError::new(format!("axis {} out of bounds for expanding NDArray with \
+                                             {} dimensions",
+                                            axis,
+                                            self.ndim())));
+        }
+        self.op("expand_dims").add_param("axis", axis).invoke()
+    }
+
+    /// Remove the given axes of size one, or all of them if `axis` is empty.
+    pub fn squeeze(&self, axis: &[usize]) -> MXResult<NDArray> {
+        let shape = self.shape();
+        let mut op = self.op("squeeze");
+        if !axis.is_empty() {
+            for a in axis {
+                try!(self.check_axis(*a));
+                if shape[*a] != 1 {
+                    return Err(MXError::new(format!("cannot squeeze axis {} of size {}",
+                                                    a,
+                                                    shape[*a])));
+                }
+            }
+            op.add_param("axis", axis);
+        }
+        op.invoke()
+    }
+
+    /// Reverse the order of the elements along `axis`.
+    pub fn flip(&self, axis: usize) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        self.op("reverse").add_param("axis", axis).invoke()
+    }
+
+    /// Pad the spatial axes of a four- or five-dimensional array.
+    /// `pad_width` holds the padding before and after each axis, and must be
+    /// zero for the first two axes.
+    pub fn pad(&self, mode: PadMode, pad_width: &[usize]) -> MXResult<NDArray> {
+        let ndim = self.ndim();
+        if ndim != 4 && ndim != 5 {
+            return Err(MXError::new("NDArray pad requires a four- or five-dimensional array"));
+        }
+        if pad_width.len() != 2 * ndim || pad_width[..4].iter().any(|w| *w != 0) {
+            return Err(MXError::new(format!("invalid pad width {:?} for NDArray of shape {:?}",
+                                            pad_width,
+                                            self.shape())));
+        }
+        let mut op = self.op("Pad");
+        op.add_param("mode", mode).add_param("pad_width", pad_width);
+        if let PadMode::Constant(value) = mode {
+            op.add_param("constant_value", value);
+        }
+        op.invoke()
+    }
+
+    /// Interchange two axes.
+    pub fn swapaxes(&self, axis1: usize, axis2: usize) -> MXResult<NDArray> {
+        try!(self.check_axis(axis1));
+        try!(self.check_axis(axis2));
+        self.op("SwapAxis")
+            .add_param("dim1", axis1)
+            .add_param("dim2", axis2)
+            .invoke()
+    }
+}
@@ -0,0 +1,151 @@
+use ndarray::{NDArray, broadcast_shape};
+use operator::Operator;
+use util::*;
+
+macro_rules! unary {
+    ( $( $op:ident, $op_name:expr, $doc:expr; )* ) => {
+        impl NDArray {
+            $(
+                #[doc = $doc]
+                pub fn $op(&self) -> MXResult<NDArray> {
+                    self.op($op_name).invoke()
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! binary {
+    ( $( $op:ident, $op_scalar:ident, $op_broadcast:ident, $op_name:expr, $doc:expr; )* ) => {
+        impl NDArray {
+            $(
+                #[doc = $doc]
+                ///
+                /// Both arrays must have the same shape.
+                pub fn $op(&self, rhs: &NDArray) -> MXResult<NDArray> {
+                    try!(self.check_same_shape(rhs));
+                    Operator::new($op_name)
+                        .add_input(self)
+                        .add_input(rhs)
+                        .invoke()
+                }
+
+                #[doc = $doc]
+                ///
+                /// The right-hand side is a scalar.
+                pub fn $op_scalar(&self, scalar: f64) -> MXResult<NDArray> {
+                    self.op(concat!($op_name, "_scalar"))
+                        .add_param("scalar", scalar)
+                        .invoke()
+                }
+
+                #[doc = $doc]
+                ///
+                /// Broadcasts axes of size one, following numpy broadcasting
+                /// rules.
+                pub fn $op_broadcast(&self, rhs: &NDArray) -> MXResult<NDArray> {
+                    try!(broadcast_shape(&self.shape(), &rhs.shape()));
+                    Operator::new(concat!("broadcast", $op_name))
+                        .add_input(self)
+                        .add_input(rhs)
+                        .invoke()
+                }
+            )*
+        }
+    };
+}
+
+unary! {
+    exp, "exp", "The elementwise exponential.";
+    expm1, "expm1", "The elementwise `exp(x) - 1`, accurate for small `x`.";
+    log, "log", "The elementwise natural logarithm.";
+    log1p, "log1p", "The elementwise `log(1 + x)`, accurate for small `x`.";
+    log2, "log2", "The elementwise base-2 logarithm.";
+    log10, "log10", "The elementwise base-10 logarithm.";
+    sqrt, "sqrt", "The elementwise square root.";
+    rsqrt, "rsqrt", "The elementwise reciprocal square root.";
+    cbrt, "cbrt", "The elementwise cube root.";
+    square, "square", "The elementwise square.";
+    reciprocal, "reciprocal", "The elementwise reciprocal.";
+    abs, "abs", "The elementwise absolute value.";
+    sign, "sign", "The elementwise sign, -1, 0 or 1.";
+    floor, "floor", "The elementwise floor.";
+    ceil, "ceil", "The elementwise ceiling.";
+    round, "round", "The elementwise nearest integer, rounding half away from zero.";
+    trunc, "trunc", "The elementwise integer part.";
+    sin, "sin", "The elementwise sine.";
+    cos, "cos", "The elementwise cosine.";
+    tan, "tan", "The elementwise tangent.";
+    arcsin, "arcsin", "The elementwise inverse sine.";
+    arccos, "arccos", "The elementwise inverse cosine.";
+    arctan, "arctan", "The elementwise inverse tangent.";
+    sinh, "sinh", "The elementwise hyperbolic sine.";
+    cosh, "cosh", "The elementwise hyperbolic cosine.";
+    tanh, "tanh", "The elementwise hyperbolic tangent.";
+    arcsinh, "arcsinh", "The elementwise inverse hyperbolic sine.";
+    arccosh, "arccosh", "The elementwise inverse hyperbolic cosine.";
+    arctanh, "arctanh", "The elementwise inverse hyperbolic tangent.";
+    logical_not, "logical_not", "The elementwise logical negation, as 0 or 1.";
+    relu, "relu", "The elementwise rectified linear unit, `max(x, 0)`.";
+    sigmoid, "sigmoid", "The elementwise logistic sigmoid, `1 / (1 + exp(-x))`.";
+}
+
+binary! {
+    pow, pow_scalar, broadcast_pow, "_power",
+        "The elementwise power.";
+    maximum, maximum_scalar, broadcast_maximum, "_maximum",
+        "The elementwise maximum.";
+    minimum, minimum_scalar, broadcast_minimum, "_minimum",
+        "The elementwise minimum.";
+    hypot, hypot_scalar, broadcast_hypot, "_hypot",
+        "The elementwise hypotenuse, `sqrt(x * x + y * y)`.";
+    equal, equal_scalar, broadcast_equal, "_equal",
+        "Elementwise `==`, as 0 or 1.";
+    not_equal, not_equal_scalar, broadcast_not_equal, "_not_equal",
+        "Elementwise `!=`, as 0 or 1.";
+    greater, greater_scalar, broadcast_greater, "_greater",
+        "Elementwise `>`, as 0 or 1.";
+    greater_equal, greater_equal_scalar, broadcast_greater_equal, "_greater_equal",
+        "Elementwise `>=`, as 0 or 1.";
+    lesser, lesser_scalar, broadcast_lesser, "_lesser",
+        "Elementwise `<`, as 0 or 1.";
+    lesser_equal, lesser_equal_scalar, broadcast_lesser_equal, "_lesser_equal",
+        "Elementwise `<=`, as 0 or 1.";
+    logical_and, logical_and_scalar, broadcast_logical_and, "_logical_and",
+        "The elementwise logical and, as 0 or 1.";
+    logical_or, logical_or_scalar, broadcast_logical_or, "_logical_or",
+        "The elementwise logical or, as 0 or 1.";
+    logical_xor, logical_xor_scalar, broadcast_logical_xor, "_logical_xor",
+        "The elementwise logical exclusive or, as 0 or 1.";
+}
+
+impl NDArray {
+    /// Raise `base` to the elementwise power of the array.
+    pub fn rpow_scalar(&self, base: f64) -> MXResult<NDArray> {
+        self.op("_rpower_scalar").add_param("scalar", base).invoke()
+    }
+
+    /// The softmax along `axis`, which sums to one.
+    pub fn softmax(&self, axis: usize) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        self.op("softmax").add_param("axis", axis).invoke()
+    }
+
+    /// The logarithm of the softmax along `axis`, computed in a numerically
+    /// stable way.
+    pub fn log_softmax(&self, axis: usize) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        self.op("log_softmax").add_param("axis", axis).invoke()
+    }
+
+    /// Limit the elements to the range `[min, max]`.
+    pub fn clip(&self, min: f64, max: f64) -> MXResult<NDArray> {
+        if min > max {
+            return Err(MXError::new(format!("invalid NDArray clip range [{}, {}]", min, max)));
+        }
+        self.op("clip")
+            .add_param("a_min", min)
+            .add_param("a_max", max)
+            .invoke()
+    }
+}
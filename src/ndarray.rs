@@ -1,81 +1,165 @@
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io::{Read, Write};
 use std::mem;
 use std::ops;
+use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
 use std::ptr;
 use std::slice;
+use std::str;
 
-use libc::{c_int, c_uint, c_void};
+use half::f16;
+use libc::{c_char, c_int, c_uint, c_void};
 use mxnet_sys::*;
+use operator::Operator;
+use params;
 use util::*;
 
 macro_rules! ops {
     (
         $op_name:expr,
-        $op_const:ident,
-        $op_const_scalar:ident,
+        $op_rscalar_name:expr,
+        $op_broadcast:ident,
         $op_class:ident::$op_method:ident,
         $op_assign_class:ident::$op_assign_method:ident
     ) => {
-        lazy_static! {
-            static ref $op_const: Op = Op(get_function($op_name));
-            static ref $op_const_scalar: Op = Op(get_function(concat!($op_name, "_scalar")));
+        impl NDArray {
+            /// Elementwise operation broadcasting axes of size one, following
+            /// numpy broadcasting rules.
+            pub fn $op_broadcast(&self, rhs: &NDArray) -> MXResult<NDArray> {
+                try!(broadcast_shape(&self.shape(), &rhs.shape()));
+                Operator::new(stringify!($op_broadcast))
+                    .add_input(self)
+                    .add_input(rhs)
+                    .invoke()
+            }
+        }
+        impl<'a, 'b> ops::$op_class<&'b NDArray> for &'a NDArray {
+            type Output = NDArray;
+
+            fn $op_method(self, rhs: &'b NDArray) -> NDArray {
+                must(self.check_same_shape(rhs));
+                must(Operator::new($op_name)
+                    .add_input(self)
+                    .add_input(rhs)
+                    .invoke())
+            }
         }
         impl ops::$op_class for NDArray {
             type Output = NDArray;
 
             fn $op_method(self, rhs: NDArray) -> NDArray {
-                let mut handle = ptr::null_mut();
-                let mut input_handle = vec![self.handle, rhs.handle];
-                c_must!(MXFuncInvoke($op_const.0, input_handle.as_mut_ptr(),
-                                     ptr::null_mut(), &mut handle));
-                NDArray { handle: handle }
+                (&self).$op_method(&rhs)
             }
         }
-        impl ops::$op_class<f32> for NDArray {
+        impl<'b> ops::$op_class<&'b NDArray> for NDArray {
             type Output = NDArray;
 
-            fn $op_method(self, mut scalar: f32) -> NDArray {
-                let mut handle = ptr::null_mut();
-                let mut input_handle = vec![self.handle];
-                c_must!(MXFuncInvoke($op_const_scalar.0,
-                                     input_handle.as_mut_ptr(),
-                                     &mut scalar,
-                                     &mut handle));
-                NDArray { handle: handle }
+            fn $op_method(self, rhs: &'b NDArray) -> NDArray {
+                (&self).$op_method(rhs)
+            }
+        }
+        impl<'a> ops::$op_class<NDArray> for &'a NDArray {
+            type Output = NDArray;
+
+            fn $op_method(self, rhs: NDArray) -> NDArray {
+                self.$op_method(&rhs)
+            }
+        }
+        impl<'b> ops::$op_assign_class<&'b NDArray> for NDArray {
+            fn $op_assign_method(&mut self, rhs: &'b NDArray) {
+                must(self.check_same_shape(rhs));
+                must(Operator::new($op_name)
+                    .add_input_handle(self.handle)
+                    .add_input(rhs)
+                    .invoke_handle_into(self.handle))
             }
         }
         impl ops::$op_assign_class for NDArray {
             fn $op_assign_method(&mut self, rhs: NDArray) {
-                let mut input_handle = vec![self.handle, rhs.handle];
-                c_must!(MXFuncInvoke($op_const.0, input_handle.as_mut_ptr(),
-                                     ptr::null_mut(), &mut self.handle));
+                self.$op_assign_method(&rhs)
             }
         }
-        impl ops::$op_assign_class<f32> for NDArray {
-            fn $op_assign_method(&mut self, mut scalar: f32) {
-                let mut input_handle = vec![self.handle];
-                c_must!(MXFuncInvoke($op_const_scalar.0,
-                                     input_handle.as_mut_ptr(),
-                                     &mut scalar,
-                                     &mut self.handle));
+        scalar_ops!($op_name, $op_rscalar_name, $op_class::$op_method,
+                    $op_assign_class::$op_assign_method, f32);
+        scalar_ops!($op_name, $op_rscalar_name, $op_class::$op_method,
+                    $op_assign_class::$op_assign_method, f64);
+    };
+}
+
+macro_rules! scalar_ops {
+    (
+        $op_name:expr,
+        $op_rscalar_name:expr,
+        $op_class:ident::$op_method:ident,
+        $op_assign_class:ident::$op_assign_method:ident,
+        $scalar:ty
+    ) => {
+        impl<'a> ops::$op_class<$scalar> for &'a NDArray {
+            type Output = NDArray;
+
+            fn $op_method(self, scalar: $scalar) -> NDArray {
+                must(Operator::new(concat!($op_name, "_scalar"))
+                    .add_input(self)
+                    .add_param("scalar", scalar)
+                    .invoke())
+            }
+        }
+        impl ops::$op_class<$scalar> for NDArray {
+            type Output = NDArray;
+
+            fn $op_method(self, scalar: $scalar) -> NDArray {
+                (&self).$op_method(scalar)
+            }
+        }
+        impl<'b> ops::$op_class<&'b NDArray> for $scalar {
+            type Output = NDArray;
+
+            fn $op_method(self, rhs: &'b NDArray) -> NDArray {
+                must(Operator::new($op_rscalar_name)
+                    .add_input(rhs)
+                    .add_param("scalar", self)
+                    .invoke())
+            }
+        }
+        impl ops::$op_class<NDArray> for $scalar {
+            type Output = NDArray;
+
+            fn $op_method(self, rhs: NDArray) -> NDArray {
+                self.$op_method(&rhs)
+            }
+        }
+        impl ops::$op_assign_class<$scalar> for NDArray {
+            fn $op_assign_method(&mut self, scalar: $scalar) {
+                must(Operator::new(concat!($op_name, "_scalar"))
+                    .add_input_handle(self.handle)
+                    .add_param("scalar", scalar)
+                    .invoke_handle_into(self.handle))
             }
         }
     };
 }
 
-// Force Rust to compile lazy static function handlers.
-struct Op(FunctionHandle);
-unsafe impl Sync for Op {}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum DeviceType {
     CPU = 1,
     GPU = 2,
     CPUPinned = 3,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl DeviceType {
+    pub fn from_raw(dev_type: c_int) -> MXResult<Self> {
+        match dev_type {
+            1 => Ok(DeviceType::CPU),
+            2 => Ok(DeviceType::GPU),
+            3 => Ok(DeviceType::CPUPinned),
+            _ => Err(MXError::new(format!("unknown device type {}", dev_type))),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Context {
     pub device_type: DeviceType,
     pub device_id: isize,
@@ -110,11 +194,148 @@ impl Context {
     pub fn default_cpu() -> Self {
         Self::cpu(0)
     }
+
+    /// Page-locked host memory, for faster copies to and from GPUs.
+    pub fn cpu_pinned(device_id: isize) -> Self {
+        Self::new(DeviceType::CPUPinned, device_id)
+    }
+}
+
+/// Formats the context the way mxnet names it, e.g. `gpu(0)`.
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let device_type = match self.device_type {
+            DeviceType::CPU => "cpu",
+            DeviceType::GPU => "gpu",
+            DeviceType::CPUPinned => "cpu_pinned",
+        };
+        write!(f, "{}({})", device_type, self.device_id)
+    }
+}
+
+impl str::FromStr for Context {
+    type Err = MXError;
+
+    /// Parse a context in the format written by `Display`, e.g. "gpu(1)", or
+    /// a bare device type such as "gpu" for device 0.
+    fn from_str(s: &str) -> MXResult<Self> {
+        let invalid = || MXError::new(format!("invalid context {}", s));
+        let open = s.find('(').unwrap_or(s.len());
+        let device_type = match &s[..open] {
+            "cpu" => DeviceType::CPU,
+            "gpu" => DeviceType::GPU,
+            "cpu_pinned" => DeviceType::CPUPinned,
+            _ => return Err(invalid()),
+        };
+        if open == s.len() {
+            return Ok(Context::new(device_type, 0));
+        }
+        if !s.ends_with(')') {
+            return Err(invalid());
+        }
+        let device_id = try!(s[open + 1..s.len() - 1].parse().map_err(|_| invalid()));
+        Ok(Context::new(device_type, device_id))
+    }
 }
 
+/// Element data types supported by mxnet, mirroring `mshadow::TypeFlag`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DType {
+    Float32 = 0,
+    Float64 = 1,
+    Float16 = 2,
+    UInt8 = 3,
+    Int32 = 4,
+    Int8 = 5,
+    Int64 = 6,
+}
+
+impl Default for DType {
+    fn default() -> Self {
+        DType::Float32
+    }
+}
+
+impl DType {
+    pub fn from_raw(type_flag: c_int) -> MXResult<Self> {
+        match type_flag {
+            0 => Ok(DType::Float32),
+            1 => Ok(DType::Float64),
+            2 => Ok(DType::Float16),
+            3 => Ok(DType::UInt8),
+            4 => Ok(DType::Int32),
+            5 => Ok(DType::Int8),
+            6 => Ok(DType::Int64),
+            _ => Err(MXError::new(format!("unknown NDArray data type {}", type_flag))),
+        }
+    }
+
+    /// Size of a single element in bytes.
+    pub fn size(&self) -> usize {
+        match *self {
+            DType::Float32 => 4,
+            DType::Float64 => 8,
+            DType::Float16 => 2,
+            DType::UInt8 => 1,
+            DType::Int32 => 4,
+            DType::Int8 => 1,
+            DType::Int64 => 8,
+        }
+    }
+
+    /// The name mxnet uses for the data type in operator parameters.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DType::Float32 => "float32",
+            DType::Float64 => "float64",
+            DType::Float16 => "float16",
+            DType::UInt8 => "uint8",
+            DType::Int32 => "int32",
+            DType::Int8 => "int8",
+            DType::Int64 => "int64",
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        match *self {
+            DType::UInt8 | DType::Int32 | DType::Int8 | DType::Int64 => true,
+            DType::Float32 | DType::Float64 | DType::Float16 => false,
+        }
+    }
+}
+
+/// Host types that can be copied in and out of an `NDArray`.
+///
+/// # Safety
+///
+/// The host memory of `Self` is handed to mxnet as a buffer of
+/// `Self::dtype()` elements, so the two must have the same size and layout.
+pub unsafe trait Element: Copy + 'static {
+    fn dtype() -> DType;
+}
+
+macro_rules! element {
+    ( $ty:ty, $dtype:ident ) => {
+        unsafe impl Element for $ty {
+            fn dtype() -> DType {
+                DType::$dtype
+            }
+        }
+    };
+}
+
+element!(f32, Float32);
+element!(f64, Float64);
+element!(f16, Float16);
+element!(u8, UInt8);
+element!(i32, Int32);
+element!(i8, Int8);
+element!(i64, Int64);
+
 pub struct NDArrayBuilder<'a> {
-    data: Option<&'a Vec<f32>>,
+    data: Option<(&'a [u8], DType)>,
     shape: Vec<u32>,
+    dtype: DType,
     context: Context,
     delay_alloc: bool,
 }
@@ -124,20 +345,45 @@ impl<'a> NDArrayBuilder<'a> {
         NDArrayBuilder {
             data: None,
             shape: shape,
+            dtype: Default::default(),
             context: Default::default(),
             delay_alloc: true,
         }
     }
 
-    pub fn from(data: &'a Vec<f32>) -> Self {
+    pub fn from<T: Element>(data: &'a [T]) -> Self {
+        let bytes = unsafe {
+            slice::from_raw_parts(data.as_ptr() as *const u8,
+                                  data.len() * mem::size_of::<T>())
+        };
+        Self::from_host_bytes(bytes, T::dtype())
+    }
+
+    /// Build from host data of `dtype` elements in native byte order.
+    pub(crate) fn from_host_bytes(data: &'a [u8], dtype: DType) -> Self {
         NDArrayBuilder {
-            data: Some(data),
-            shape: vec![data.len() as u32],
+            data: Some((data, dtype)),
+            shape: vec![(data.len() / dtype.size()) as u32],
+            dtype: dtype,
             context: Default::default(),
             delay_alloc: true,
         }
     }
 
+    /// Set the element type of the array. Arrays built from host data must
+    /// keep the data type of the host elements.
+    pub fn dtype(&mut self, dtype: DType) -> &mut Self {
+        self.dtype = dtype;
+        self
+    }
+
+    /// Set the shape of the array. Arrays built from host data default to a
+    /// one-dimensional shape; the number of elements must match the data.
+    pub fn shape(&mut self, shape: Vec<u32>) -> &mut Self {
+        self.shape = shape;
+        self
+    }
+
     pub fn context(&mut self, context: Context) -> &mut Self {
         self.context = context;
         self
@@ -150,28 +396,90 @@ impl<'a> NDArrayBuilder<'a> {
 
     pub fn create(&self) -> MXResult<NDArray> {
         let mut handle = ptr::null_mut();
-        c_try!(MXNDArrayCreate(self.shape.as_ptr(),
-                               self.shape.len() as c_uint,
-                               self.context.device_type as c_int,
-                               self.context.device_id as c_int,
-                               (!self.data.is_some() && self.delay_alloc) as c_int,
-                               &mut handle));
         match self.data {
-            Some(data) => {
-                c_try!(MXNDArraySyncCopyFromCPU(handle,
-                                                data.as_ptr() as *const c_void,
-                                                data.len() * mem::size_of::<f32>()));
+            Some((_, dtype)) if dtype != self.dtype => {
+                return Err(MXError::new("NDArray data type mismatch"));
+            }
+            Some((data, dtype)) => {
+                let size = self.shape.iter().fold(1, |acc, x| acc * *x as usize);
+                if data.len() / dtype.size() != size {
+                    return Err(MXError::new("NDArray size mismatch"));
+                }
+            }
+            _ => {}
+        }
+        c_try!(MXNDArrayCreateEx(self.shape.as_ptr(),
+                                 self.shape.len() as c_uint,
+                                 self.context.device_type as c_int,
+                                 self.context.device_id as c_int,
+                                 (!self.data.is_some() && self.delay_alloc) as c_int,
+                                 self.dtype as c_int,
+                                 &mut handle));
+        let mut arr = NDArray { handle: handle };
+        match self.data {
+            Some((data, dtype)) => {
+                try!(arr.sync_copy_from_raw(data.as_ptr() as *const c_void,
+                                            data.len() / dtype.size()));
             }
             _ => {}
         }
-        Ok(NDArray { handle: handle })
+        Ok(arr)
+    }
+}
+
+/// Selects part of one axis of an `NDArray` in `NDArray::view`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Index {
+    /// A single position. The axis is removed from the result.
+    At(usize),
+    /// The half-open range `[begin, end)`.
+    Range(usize, usize),
+    /// The whole axis.
+    All,
+}
+
+impl From<usize> for Index {
+    fn from(index: usize) -> Self {
+        Index::At(index)
+    }
+}
+
+impl From<Range<usize>> for Index {
+    fn from(range: Range<usize>) -> Self {
+        Index::Range(range.start, range.end)
+    }
+}
+
+impl From<RangeFrom<usize>> for Index {
+    fn from(range: RangeFrom<usize>) -> Self {
+        Index::Range(range.start, usize::max_value())
+    }
+}
+
+impl From<RangeTo<usize>> for Index {
+    fn from(range: RangeTo<usize>) -> Self {
+        Index::Range(0, range.end)
+    }
+}
+
+impl From<RangeFull> for Index {
+    fn from(_: RangeFull) -> Self {
+        Index::All
     }
 }
 
 pub struct NDArray {
-    handle: NDArrayHandle,
+    pub(crate) handle: NDArrayHandle,
 }
 
+// An NDArray handle is not tied to the thread that created it, and the engine
+// orders the operations pushed on an array whichever thread pushes them. The
+// C API itself is not thread-safe, so `NDArray` is not `Sync`: a handle is
+// only ever used by the one thread owning the `NDArray`. This lets
+// `CustomOp`s and autograd `Function`s, which mxnet calls from its worker
+// threads, keep arrays.
+unsafe impl Send for NDArray {}
+
 impl NDArray {
     pub fn new() -> MXResult<Self> {
         let mut handle = ptr::null_mut();
@@ -179,37 +487,122 @@ impl NDArray {
         Ok(NDArray { handle: handle })
     }
 
-    pub fn from(data: &Vec<f32>) -> MXResult<Self> {
-        let mut handle = ptr::null_mut();
-        c_try!(MXNDArrayCreateNone(&mut handle));
-        c_try!(MXNDArraySyncCopyFromCPU(handle, data.as_ptr() as *const c_void, data.len()));
-        Ok(NDArray { handle: handle })
+    pub fn from<T: Element>(data: &[T]) -> MXResult<Self> {
+        NDArrayBuilder::from(data).create()
+    }
+
+    /// A `float32` array of zeros.
+    pub fn zeros(shape: &[usize], context: Context) -> MXResult<Self> {
+        Operator::new("_zeros")
+            .add_param("shape", shape)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// A `float32` array of ones.
+    pub fn ones(shape: &[usize], context: Context) -> MXResult<Self> {
+        Operator::new("_ones")
+            .add_param("shape", shape)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// A `float32` array with every element set to `value`.
+    pub fn full(shape: &[usize], value: f64, context: Context) -> MXResult<Self> {
+        Operator::new("_full")
+            .add_param("shape", shape)
+            .add_param("value", value)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// A one-dimensional `float32` array of evenly spaced values in
+    /// `[start, stop)`.
+    pub fn arange(start: f64, stop: f64, step: f64, context: Context) -> MXResult<Self> {
+        Operator::new("_arange")
+            .add_param("start", start)
+            .add_param("stop", stop)
+            .add_param("step", step)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// A one-dimensional `float32` array of `num` evenly spaced values in
+    /// `[start, stop]`.
+    pub fn linspace(start: f64, stop: f64, num: usize, context: Context) -> MXResult<Self> {
+        Operator::new("_linspace")
+            .add_param("start", start)
+            .add_param("stop", stop)
+            .add_param("num", num)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// A two-dimensional `float32` identity matrix of size `n`.
+    pub fn eye(n: usize, context: Context) -> MXResult<Self> {
+        Operator::new("_eye")
+            .add_param("N", n)
+            .add_param("ctx", context)
+            .invoke()
+    }
+
+    /// An array of zeros with the shape, data type and context of `self`.
+    pub fn zeros_like(&self) -> MXResult<Self> {
+        self.op("zeros_like").invoke()
+    }
+
+    /// An array of ones with the shape, data type and context of `self`.
+    pub fn ones_like(&self) -> MXResult<Self> {
+        self.op("ones_like").invoke()
+    }
+
+    /// A copy of the array converted to another element type.
+    pub fn astype(&self, dtype: DType) -> MXResult<Self> {
+        self.op("Cast").add_param("dtype", dtype).invoke()
     }
 
-    fn load_impl(file_name: &str, with_names: bool) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
+    fn load_impl(file_name: &str) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
         let c_file_name = CString::new(file_name).unwrap();
         let mut out_size = 0;
         let mut out_arr = ptr::null_mut();
         let mut out_name_size = 0;
         let mut out_names = ptr::null();
-        let out_names_ptr = if with_names {
-            &mut out_names
-        } else {
-            ptr::null_mut()
-        };
         c_try!(MXNDArrayLoad(c_file_name.as_ptr(),
                              &mut out_size,
                              &mut out_arr,
                              &mut out_name_size,
-                             out_names_ptr));
+                             &mut out_names));
+        Self::collect_loaded(out_size, out_arr, out_name_size, out_names)
+    }
+
+    fn load_bytes_impl(buf: &[u8]) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
+        let mut out_size = 0;
+        let mut out_arr = ptr::null_mut();
+        let mut out_name_size = 0;
+        let mut out_names = ptr::null();
+        c_try!(MXNDArrayLoadFromBuffer(buf.as_ptr() as *const c_void,
+                                       buf.len(),
+                                       &mut out_size,
+                                       &mut out_arr,
+                                       &mut out_name_size,
+                                       &mut out_names));
+        Self::collect_loaded(out_size, out_arr, out_name_size, out_names)
+    }
 
-        let out_slice = unsafe { slice::from_raw_parts(out_arr, out_size as usize) };
+    fn collect_loaded(out_size: mx_uint,
+                      out_arr: *mut NDArrayHandle,
+                      out_name_size: mx_uint,
+                      out_names: *const *const c_char)
+                      -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
         let mut out_vec = Vec::with_capacity(out_size as usize);
-        for handle_ptr in out_slice {
-            out_vec.push(NDArray { handle: *handle_ptr });
+        if out_size > 0 {
+            let out_slice = unsafe { slice::from_raw_parts(out_arr, out_size as usize) };
+            for handle_ptr in out_slice {
+                out_vec.push(NDArray { handle: *handle_ptr });
+            }
         }
 
-        let out_names_vec = if with_names && out_name_size > 0 {
+        let out_names_vec = if out_name_size > 0 {
             if out_name_size != out_size {
                 return Err(MXError::new("NDArray load with names size mismatch"));
             }
@@ -228,57 +621,128 @@ impl NDArray {
         Ok((out_vec, out_names_vec))
     }
 
+    fn into_map(loaded: (Vec<Self>, Option<Vec<String>>)) -> MXResult<HashMap<String, Self>> {
+        match loaded {
+            (arrs, Some(names)) => Ok(names.into_iter().zip(arrs).collect()),
+            _ => Err(MXError::new("NDArray load missing names")),
+        }
+    }
+
     pub fn load(file_name: &str) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
-        Self::load_impl(file_name, true)
+        Self::load_impl(file_name)
     }
 
     pub fn load_list(file_name: &str) -> MXResult<Vec<Self>> {
-        Ok(try!(Self::load_impl(file_name, false)).0)
+        Ok(try!(Self::load_impl(file_name)).0)
     }
 
     pub fn load_map(file_name: &str) -> MXResult<HashMap<String, Self>> {
-        match try!(Self::load_impl(file_name, false)) {
-            (mut arrs, Some(mut names)) => {
-                let mut map = HashMap::with_capacity(arrs.len());
-                while !arrs.is_empty() {
-                    map.insert(names.remove(0), arrs.remove(0));
-                }
-                Ok(map)
-            }
-            _ => Err(MXError::new("NDArray load missing names")),
-        }
+        Self::into_map(try!(Self::load_impl(file_name)))
     }
 
-    pub fn save_list(file_name: &str, array_list: &Vec<Self>) -> MXResult<()> {
+    /// Load arrays from a buffer in the format written by `save_list` and
+    /// `save_map`.
+    pub fn load_bytes(buf: &[u8]) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
+        Self::load_bytes_impl(buf)
+    }
+
+    pub fn load_list_bytes(buf: &[u8]) -> MXResult<Vec<Self>> {
+        Ok(try!(Self::load_bytes_impl(buf)).0)
+    }
+
+    pub fn load_map_bytes(buf: &[u8]) -> MXResult<HashMap<String, Self>> {
+        Self::into_map(try!(Self::load_bytes_impl(buf)))
+    }
+
+    /// Load arrays from a reader in the format written by `save_list` and
+    /// `save_map`.
+    pub fn read<R: Read>(reader: &mut R) -> MXResult<(Vec<Self>, Option<Vec<String>>)> {
+        let mut buf = Vec::new();
+        try!(reader.read_to_end(&mut buf));
+        Self::load_bytes_impl(&buf)
+    }
+
+    pub fn read_list<R: Read>(reader: &mut R) -> MXResult<Vec<Self>> {
+        Ok(try!(Self::read(reader)).0)
+    }
+
+    pub fn read_map<R: Read>(reader: &mut R) -> MXResult<HashMap<String, Self>> {
+        Self::into_map(try!(Self::read(reader)))
+    }
+
+    fn save_impl(file_name: &str,
+                 mut args: Vec<NDArrayHandle>,
+                 names: Option<Vec<CString>>)
+                 -> MXResult<()> {
         let c_file_name = CString::new(file_name).unwrap();
-        let num_args = array_list.len();
-        let mut args = Vec::with_capacity(num_args);
-        for arr in array_list.iter() {
-            args.push(arr.handle);
-        }
+        let c_names: Option<Vec<*const c_char>> =
+            names.as_ref().map(|names| names.iter().map(|name| name.as_ptr()).collect());
         c_try!(MXNDArraySave(c_file_name.as_ptr(),
-                             num_args as u32,
+                             args.len() as u32,
                              args.as_mut_ptr(),
-                             ptr::null()));
+                             c_names.as_ref().map_or(ptr::null(), |names| names.as_ptr())));
         Ok(())
     }
 
+    pub fn save_list(file_name: &str, array_list: &Vec<Self>) -> MXResult<()> {
+        let args = array_list.iter().map(|arr| arr.handle).collect();
+        Self::save_impl(file_name, args, None)
+    }
+
     pub fn save_map(file_name: &str, array_map: &HashMap<String, Self>) -> MXResult<()> {
-        let c_file_name = CString::new(file_name).unwrap();
-        let num_args = array_map.len();
-        let mut args = Vec::with_capacity(num_args);
-        let mut names = Vec::with_capacity(num_args);
+        let mut args = Vec::with_capacity(array_map.len());
+        let mut names = Vec::with_capacity(array_map.len());
         for (name, arr) in array_map.iter() {
             args.push(arr.handle);
-            names.push(CString::new(name.as_str()).unwrap().as_ptr());
+            names.push(CString::new(name.as_str()).unwrap());
         }
-        c_try!(MXNDArraySave(c_file_name.as_ptr(),
-                             num_args as u32,
-                             args.as_mut_ptr(),
-                             names.as_ptr()));
+        Self::save_impl(file_name, args, Some(names))
+    }
+
+    /// Serialize arrays into the format written by `save_list` and `save_map`,
+    /// with each array serialized by `to_bytes`.
+    fn save_bytes_impl(arrays: Vec<&Self>, names: Vec<&str>) -> MXResult<Vec<u8>> {
+        params::write_list(&arrays, &names, |buf, arr| {
+            buf.extend_from_slice(&try!(arr.to_bytes()));
+            Ok(())
+        })
+    }
+
+    pub fn save_list_bytes(array_list: &Vec<Self>) -> MXResult<Vec<u8>> {
+        Self::save_bytes_impl(array_list.iter().collect(), Vec::new())
+    }
+
+    pub fn save_map_bytes(array_map: &HashMap<String, Self>) -> MXResult<Vec<u8>> {
+        let (names, arrays) = array_map.iter().map(|(name, arr)| (name.as_str(), arr)).unzip();
+        Self::save_bytes_impl(arrays, names)
+    }
+
+    pub fn write_list<W: Write>(writer: &mut W, array_list: &Vec<Self>) -> MXResult<()> {
+        try!(writer.write_all(&try!(Self::save_list_bytes(array_list))));
         Ok(())
     }
 
+    pub fn write_map<W: Write>(writer: &mut W, array_map: &HashMap<String, Self>) -> MXResult<()> {
+        try!(writer.write_all(&try!(Self::save_map_bytes(array_map))));
+        Ok(())
+    }
+
+    /// Serialize a single array, including its shape, context and data type.
+    pub fn to_bytes(&self) -> MXResult<Vec<u8>> {
+        let mut out_size = 0;
+        let mut out_buf = ptr::null();
+        c_try!(MXNDArraySaveRawBytes(self.handle, &mut out_size, &mut out_buf));
+        let buf = unsafe { slice::from_raw_parts(out_buf as *const u8, out_size) };
+        Ok(buf.to_vec())
+    }
+
+    /// Deserialize a single array written by `to_bytes`.
+    pub fn from_bytes(buf: &[u8]) -> MXResult<Self> {
+        let mut handle = ptr::null_mut();
+        c_try!(MXNDArrayLoadFromRawBytes(buf.as_ptr() as *const c_void, buf.len(), &mut handle));
+        Ok(NDArray { handle: handle })
+    }
+
     pub fn size(&self) -> usize {
         self.raw_shape().iter().fold(1, |acc, x| acc * *x as usize)
     }
@@ -292,6 +756,191 @@ impl NDArray {
         ret
     }
 
+    /// The element data type. Fails for data types this crate does not
+    /// support.
+    pub fn dtype(&self) -> MXResult<DType> {
+        let mut out_dtype = 0;
+        c_try!(MXNDArrayGetDType(self.handle, &mut out_dtype));
+        DType::from_raw(out_dtype)
+    }
+
+    /// Copy host data into the array. The element type and the number of
+    /// elements must match the array.
+    pub fn sync_copy_from<T: Element>(&mut self, data: &[T]) -> MXResult<()> {
+        try!(self.check_dtype::<T>());
+        if data.len() != self.size() {
+            return Err(MXError::new("NDArray size mismatch"));
+        }
+        self.sync_copy_from_raw(data.as_ptr() as *const c_void, data.len())
+    }
+
+    fn sync_copy_from_raw(&mut self, data: *const c_void, size: usize) -> MXResult<()> {
+        c_try!(MXNDArraySyncCopyFromCPU(self.handle, data, size));
+        Ok(())
+    }
+
+    /// Copy the array contents into a host slice. The element type and the
+    /// number of elements must match the array.
+    ///
+    /// The copy blocks until all pending engine writes to the array finish.
+    pub fn copy_to_slice<T: Element>(&self, data: &mut [T]) -> MXResult<()> {
+        try!(self.check_dtype::<T>());
+        if data.len() != self.size() {
+            return Err(MXError::new("NDArray size mismatch"));
+        }
+        c_try!(MXNDArraySyncCopyToCPU(self.handle,
+                                      data.as_mut_ptr() as *mut c_void,
+                                      data.len()));
+        Ok(())
+    }
+
+    /// Copy the array contents into a new host vector in row-major order.
+    ///
+    /// The copy blocks until all pending engine writes to the array finish.
+    pub fn to_vec<T: Element>(&self) -> MXResult<Vec<T>> {
+        try!(self.check_dtype::<T>());
+        let size = self.size();
+        let mut data = Vec::with_capacity(size);
+        c_try!(MXNDArraySyncCopyToCPU(self.handle,
+                                      data.as_mut_ptr() as *mut c_void,
+                                      size));
+        unsafe { data.set_len(size) };
+        Ok(data)
+    }
+
+    /// Copy the array contents into a new host vector, together with the shape
+    /// needed to interpret it.
+    pub fn to_vec_with_shape<T: Element>(&self) -> MXResult<(Vec<usize>, Vec<T>)> {
+        let shape = self.shape();
+        let data = try!(self.to_vec());
+        Ok((shape, data))
+    }
+
+    /// Copy the array contents into host memory as native-endian bytes.
+    pub(crate) fn host_bytes(&self) -> MXResult<Vec<u8>> {
+        let size = self.size();
+        let mut data = vec![0u8; size * try!(self.dtype()).size()];
+        c_try!(MXNDArraySyncCopyToCPU(self.handle,
+                                      data.as_mut_ptr() as *mut c_void,
+                                      size));
+        Ok(data)
+    }
+
+    /// Borrow the contents of a CPU array without copying.
+    ///
+    /// Waits for pending engine writes to the array before returning. Fails
+    /// for arrays that do not live in host memory.
+    ///
+    /// # Safety
+    ///
+    /// Arrays returned by `reshape`, `at`, `slice` and `view` share memory
+    /// with this one. The caller must not write to the array or any array
+    /// sharing its memory while the slice is alive.
+    pub unsafe fn as_slice<T: Element>(&self) -> MXResult<&[T]> {
+        try!(self.check_dtype::<T>());
+        try!(self.check_host_memory());
+        try!(self.wait_to_read());
+        let size = self.size();
+        if size == 0 {
+            return Ok(&[]);
+        }
+        let data = try!(self.raw_data());
+        Ok(slice::from_raw_parts(data as *const T, size))
+    }
+
+    /// Mutably borrow the contents of a CPU array without copying.
+    ///
+    /// Waits for pending engine reads and writes of the array before
+    /// returning. Fails for arrays that do not live in host memory.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not access any array sharing memory with this one
+    /// while the slice is alive, see `as_slice`.
+    pub unsafe fn as_mut_slice<T: Element>(&mut self) -> MXResult<&mut [T]> {
+        try!(self.check_dtype::<T>());
+        try!(self.check_host_memory());
+        try!(self.wait_to_write());
+        let size = self.size();
+        if size == 0 {
+            return Ok(&mut []);
+        }
+        let data = try!(self.raw_data());
+        Ok(slice::from_raw_parts_mut(data as *mut T, size))
+    }
+
+    fn raw_data(&self) -> MXResult<*mut c_void> {
+        let mut out_pdata = ptr::null_mut();
+        c_try!(MXNDArrayGetData(self.handle, &mut out_pdata));
+        Ok(out_pdata)
+    }
+
+    /// The device the array lives on. Fails for device types this crate does
+    /// not support.
+    pub fn context(&self) -> MXResult<Context> {
+        let mut out_dev_type = 0;
+        let mut out_dev_id = 0;
+        c_try!(MXNDArrayGetContext(self.handle, &mut out_dev_type, &mut out_dev_id));
+        Ok(Context::new(try!(DeviceType::from_raw(out_dev_type)),
+                        out_dev_id as isize))
+    }
+
+    /// Copy the contents of the array into `other`, which may live on another
+    /// device. The shapes must match.
+    pub fn copy_to(&self, other: &mut NDArray) -> MXResult<()> {
+        other.assign(self)
+    }
+
+    /// A copy of the array on `context`.
+    pub fn copy_to_context(&self, context: Context) -> MXResult<NDArray> {
+        let mut other = try!(NDArrayBuilder::new(self.raw_shape().to_vec())
+            .dtype(try!(self.dtype()))
+            .context(context)
+            .create());
+        try!(self.copy_to(&mut other));
+        Ok(other)
+    }
+
+    /// The array moved to `context`. Returns the array itself without copying
+    /// if it already lives there.
+    pub fn as_in_context(self, context: Context) -> MXResult<NDArray> {
+        if try!(self.context()) == context {
+            return Ok(self);
+        }
+        self.copy_to_context(context)
+    }
+
+    fn check_host_memory(&self) -> MXResult<()> {
+        match try!(self.context()).device_type {
+            DeviceType::CPU | DeviceType::CPUPinned => Ok(()),
+            _ => Err(MXError::new("NDArray is not in host memory")),
+        }
+    }
+
+    /// Block until all pending engine writes to the array have finished.
+    ///
+    /// Errors raised asynchronously by those writes are returned here.
+    pub fn wait_to_read(&self) -> MXResult<()> {
+        c_try!(MXNDArrayWaitToRead(self.handle));
+        Ok(())
+    }
+
+    /// Block until all pending engine reads and writes of the array have
+    /// finished.
+    ///
+    /// Errors raised asynchronously by those operations are returned here.
+    pub fn wait_to_write(&self) -> MXResult<()> {
+        c_try!(MXNDArrayWaitToWrite(self.handle));
+        Ok(())
+    }
+
+    fn check_dtype<T: Element>(&self) -> MXResult<()> {
+        if try!(self.dtype()) != T::dtype() {
+            return Err(MXError::new("NDArray data type mismatch"));
+        }
+        Ok(())
+    }
+
     pub fn reshape(&self, shape: Vec<i32>) -> MXResult<Self> {
         let mut handle = ptr::null_mut();
         c_try!(MXNDArrayReshape(self.handle,
@@ -301,6 +950,150 @@ impl NDArray {
         Ok(NDArray { handle: handle })
     }
 
+    /// A view of the `index`-th sub-array along the first axis.
+    pub fn at(&self, index: usize) -> MXResult<Self> {
+        if index >= self.leading_dim() {
+            return Err(MXError::new("NDArray index out of bounds"));
+        }
+        let mut handle = ptr::null_mut();
+        c_try!(MXNDArrayAt(self.handle, index as mx_uint, &mut handle));
+        Ok(NDArray { handle: handle })
+    }
+
+    /// A view of the sub-arrays `[begin, end)` along the first axis.
+    pub fn slice(&self, begin: usize, end: usize) -> MXResult<Self> {
+        if begin > end || end > self.leading_dim() {
+            return Err(MXError::new("NDArray slice out of bounds"));
+        }
+        let mut handle = ptr::null_mut();
+        c_try!(MXNDArraySlice(self.handle,
+                              begin as mx_uint,
+                              end as mx_uint,
+                              &mut handle));
+        Ok(NDArray { handle: handle })
+    }
+
+    /// A view of `[begin, end)` along `axis`, keeping the other axes whole.
+    ///
+    /// See `view` for when this can share memory with the array.
+    pub fn slice_axis(&self, axis: usize, begin: usize, end: usize) -> MXResult<Self> {
+        try!(self.check_axis(axis));
+        let mut indices = vec![Index::All; self.ndim()];
+        indices[axis] = Index::Range(begin, end);
+        self.view(&indices)
+    }
+
+    /// A view selecting part of each leading axis; trailing axes without an
+    /// index are kept whole.
+    ///
+    /// mxnet views must be contiguous in memory: every axis before the first
+    /// one selecting more than a single element must select one element, and
+    /// every axis after it must be selected whole. Other selections fail
+    /// instead of silently copying.
+    pub fn view(&self, indices: &[Index]) -> MXResult<Self> {
+        let shape = self.shape();
+        if indices.len() > shape.len() {
+            return Err(MXError::new("NDArray has fewer axes than indices"));
+        }
+
+        let mut out_shape = Vec::with_capacity(shape.len());
+        let mut offset = 0;
+        let mut size = 1;
+        let mut stride = self.size();
+        let mut selecting = false;
+        for (axis, &dim) in shape.iter().enumerate() {
+            let index = indices.get(axis).cloned().unwrap_or(Index::All);
+            let (begin, end) = match index {
+                Index::At(i) if i < dim => (i, i + 1),
+                Index::At(_) => return Err(MXError::new("NDArray view out of bounds")),
+                Index::Range(begin, end) => (begin, if end > dim { dim } else { end }),
+                Index::All => (0, dim),
+            };
+            if begin >= end || end > dim {
+                return Err(MXError::new("NDArray view out of bounds"));
+            }
+            if selecting && end - begin != dim {
+                return Err(MXError::new("NDArray view is not contiguous"));
+            }
+            if end - begin != 1 {
+                selecting = true;
+            }
+            stride /= dim;
+            offset += begin * stride;
+            size *= end - begin;
+            match index {
+                Index::At(_) => {}
+                _ => out_shape.push((end - begin) as i32),
+            }
+        }
+        if out_shape.is_empty() {
+            out_shape.push(1);
+        }
+
+        let flat = try!(self.reshape(vec![self.size() as i32]));
+        let sliced = try!(flat.slice(offset, offset + size));
+        sliced.reshape(out_shape)
+    }
+
+    /// Copy the contents of `other` into the array, e.g. into a view returned
+    /// by `slice`. The shapes must match.
+    pub fn assign(&mut self, other: &NDArray) -> MXResult<()> {
+        try!(self.check_same_shape(other));
+        Operator::new("_copyto").add_input(other).invoke_into(self)
+    }
+
+    /// Set every element of the array to `value`.
+    pub fn fill(&mut self, value: f32) -> MXResult<()> {
+        let shape = self.shape();
+        let dtype = try!(self.dtype());
+        Operator::new("_full")
+            .add_param("shape", shape)
+            .add_param("dtype", dtype)
+            .add_param("value", value)
+            .invoke_into(self)
+    }
+
+    /// The number of dimensions of the array.
+    pub fn ndim(&self) -> usize {
+        self.raw_shape().len()
+    }
+
+    /// The value of an array with a single element, converted to `f32`.
+    pub fn to_scalar(&self) -> MXResult<f32> {
+        if self.size() != 1 {
+            return Err(MXError::new(format!("NDArray of shape {:?} is not a scalar",
+                                            self.shape())));
+        }
+        let data = if try!(self.dtype()) == DType::Float32 {
+            try!(self.to_vec())
+        } else {
+            try!(try!(self.astype(DType::Float32)).to_vec())
+        };
+        Ok(data[0])
+    }
+
+    pub(crate) fn check_axis(&self, axis: usize) -> MXResult<()> {
+        let ndim = self.ndim();
+        if axis >= ndim {
+            return Err(MXError::new(format!("axis {} out of bounds for NDArray with {} dimensions",
+                                            axis,
+                                            ndim)));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_same_shape(&self, other: &NDArray) -> MXResult<()> {
+        let (lhs, rhs) = (self.shape(), other.shape());
+        if lhs != rhs {
+            return Err(MXError::new(format!("NDArray shape mismatch: {:?} and {:?}", lhs, rhs)));
+        }
+        Ok(())
+    }
+
+    fn leading_dim(&self) -> usize {
+        self.raw_shape().first().map_or(0, |dim| *dim as usize)
+    }
+
     fn raw_shape(&self) -> &[mx_uint] {
         let mut out_pdata = ptr::null();
         let mut out_dim = 0;
@@ -309,6 +1102,13 @@ impl NDArray {
     }
 }
 
+/// Deep copies the array within its context.
+impl Clone for NDArray {
+    fn clone(&self) -> Self {
+        must(self.context().and_then(|context| self.copy_to_context(context)))
+    }
+}
+
 impl Drop for NDArray {
     fn drop(&mut self) {
         c_must!(MXNDArrayFree(self.handle));
@@ -316,25 +1116,86 @@ impl Drop for NDArray {
 }
 
 ops!("_plus",
-     OP_PLUS,
-     OP_PLUS_SCALAR,
+     "_plus_scalar",
+     broadcast_add,
      Add::add,
      AddAssign::add_assign);
 
 ops!("_minus",
-     OP_MINUS,
-     OP_MINUS_SCALAR,
+     "_rminus_scalar",
+     broadcast_sub,
      Sub::sub,
      SubAssign::sub_assign);
 
 ops!("_div",
-     OP_DIV,
-     OP_DIV_SCALAR,
+     "_rdiv_scalar",
+     broadcast_div,
      Div::div,
      DivAssign::div_assign);
 
 ops!("_mul",
-     OP_MUL,
-     OP_MUL_SCALAR,
+     "_mul_scalar",
+     broadcast_mul,
      Mul::mul,
      MulAssign::mul_assign);
+
+impl<'a> ops::Neg for &'a NDArray {
+    type Output = NDArray;
+
+    fn neg(self) -> NDArray {
+        must(Operator::new("negative").add_input(self).invoke())
+    }
+}
+
+impl ops::Neg for NDArray {
+    type Output = NDArray;
+
+    fn neg(self) -> NDArray {
+        -&self
+    }
+}
+
+/// The shape of the result of broadcasting arrays of shapes `lhs` and `rhs`.
+pub(crate) fn broadcast_shape(lhs: &[usize], rhs: &[usize]) -> MXResult<Vec<usize>> {
+    let ndim = if lhs.len() > rhs.len() { lhs.len() } else { rhs.len() };
+    let mut shape = vec![0; ndim];
+    for i in 0..ndim {
+        let l = if i < lhs.len() { lhs[lhs.len() - 1 - i] } else { 1 };
+        let r = if i < rhs.len() { rhs[rhs.len() - 1 - i] } else { 1 };
+        shape[ndim - 1 - i] = if l == r || r == 1 {
+            l
+        } else if l == 1 {
+            r
+        } else {
+            return Err(MXError::new(format!("NDArray shapes {:?} and {:?} cannot be broadcast",
+                                            lhs,
+                                            rhs)));
+        };
+    }
+    Ok(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, broadcast_shape};
+
+    #[test]
+    fn broadcast_shapes() {
+        assert_eq!(broadcast_shape(&[2, 3], &[2, 3]).unwrap(), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[2, 1], &[1, 3]).unwrap(), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[4, 2, 3], &[3]).unwrap(), vec![4, 2, 3]);
+        assert!(broadcast_shape(&[2, 3], &[3, 2]).is_err());
+    }
+
+    #[test]
+    fn parse_context() {
+        for context in &[Context::cpu(0), Context::gpu(1), Context::cpu_pinned(0)] {
+            assert_eq!(context.to_string().parse::<Context>().unwrap(), *context);
+        }
+        assert!("tpu(0)".parse::<Context>().is_err());
+        assert!("gpu(1".parse::<Context>().is_err());
+        // mxnet passes bare device types to custom operators.
+        assert_eq!("cpu".parse::<Context>().unwrap(), Context::cpu(0));
+        assert_eq!("gpu".parse::<Context>().unwrap(), Context::gpu(0));
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use zip::write::FileOptions;
+
+use ndarray::{DType, NDArray, NDArrayBuilder};
+use util::*;
+
+const MAGIC: &'static [u8] = b"\x93NUMPY";
+
+/// A C-order array in the NumPy `.npy` format, with little-endian data.
+#[derive(Debug, PartialEq)]
+struct NpyArray {
+    dtype: DType,
+    shape: Vec<usize>,
+    data: Vec<u8>,
+}
+
+fn descr(dtype: DType) -> &'static str {
+    match dtype {
+        DType::Float32 => "<f4",
+        DType::Float64 => "<f8",
+        DType::Float16 => "<f2",
+        DType::UInt8 => "|u1",
+        DType::Int32 => "<i4",
+        DType::Int8 => "|i1",
+        DType::Int64 => "<i8",
+    }
+}
+
+fn parse_descr(descr: &str) -> MXResult<DType> {
+    let (byte_order, kind) = match descr.get(..1) {
+        Some(byte_order) => (byte_order, &descr[1..]),
+        None => return Err(MXError::new(format!("unsupported npy data type '{}'", descr))),
+    };
+    let little_endian = match byte_order {
+        "<" | "|" => true,
+        "=" => cfg!(target_endian = "little"),
+        _ => false,
+    };
+    let dtype = match kind {
+        "f4" => DType::Float32,
+        "f8" => DType::Float64,
+        "f2" => DType::Float16,
+        "u1" => DType::UInt8,
+        "i4" => DType::Int32,
+        "i1" => DType::Int8,
+        "i8" => DType::Int64,
+        _ => return Err(MXError::new(format!("unsupported npy data type '{}'", descr))),
+    };
+    if !little_endian && dtype.size() > 1 {
+        return Err(MXError::new("big-endian npy data is not supported"));
+    }
+    Ok(dtype)
+}
+
+/// The text following `'key':` in a header dictionary.
+fn header_value<'a>(header: &'a str, key: &str) -> MXResult<&'a str> {
+    let pattern = format!("'{}':", key);
+    match header.find(&pattern) {
+        Some(pos) => Ok(header[pos + pattern.len()..].trim_start()),
+        None => Err(MXError::new(format!("npy header is missing '{}'", key))),
+    }
+}
+
+fn parse_header(header: &str) -> MXResult<(DType, Vec<usize>)> {
+    let descr = try!(header_value(header, "descr"));
+    let descr = match descr.get(1..).and_then(|d| d.find('\'').map(|end| &d[..end])) {
+        Some(descr) if descr.len() > 1 => descr,
+        _ => return Err(MXError::new("malformed npy data type")),
+    };
+    let dtype = try!(parse_descr(descr));
+
+    if try!(header_value(header, "fortran_order")).starts_with("True") {
+        return Err(MXError::new("Fortran-order npy arrays are not supported"));
+    }
+
+    let shape = try!(header_value(header, "shape"));
+    let shape = match (shape.find('('), shape.find(')')) {
+        (Some(0), Some(end)) => &shape[1..end],
+        _ => return Err(MXError::new("malformed npy shape")),
+    };
+    let mut dims = Vec::new();
+    for dim in shape.split(',').map(|dim| dim.trim()).filter(|dim| !dim.is_empty()) {
+        match dim.trim_end_matches('L').parse() {
+            Ok(dim) => dims.push(dim),
+            Err(_) => return Err(MXError::new("malformed npy shape")),
+        }
+    }
+    Ok((dtype, dims))
+}
+
+fn format_header(dtype: DType, shape: &[usize]) -> String {
+    let shape = match shape.len() {
+        1 => format!("({},)", shape[0]),
+        _ => {
+            let dims: Vec<String> = shape.iter().map(|dim| dim.to_string()).collect();
+            format!("({})", dims.join(", "))
+        }
+    };
+    format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+            descr(dtype),
+            shape)
+}
+
+/// Read exactly `len` bytes, without trusting `len` for the allocation.
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> MXResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    try!(reader.take(len as u64).read_to_end(&mut buf));
+    if buf.len() != len {
+        return Err(MXError::new("unexpected end of npy file"));
+    }
+    Ok(buf)
+}
+
+fn read_npy<R: Read>(reader: &mut R) -> MXResult<NpyArray> {
+    let mut preamble = [0u8; 8];
+    try!(reader.read_exact(&mut preamble));
+    if &preamble[..6] != MAGIC {
+        return Err(MXError::new("not an npy file"));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            try!(reader.read_exact(&mut len));
+            len[0] as usize | (len[1] as usize) << 8
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            try!(reader.read_exact(&mut len));
+            len.iter().rev().fold(0, |acc, x| acc << 8 | *x as usize)
+        }
+        _ => return Err(MXError::new("unsupported npy format version")),
+    };
+    let header = try!(read_bytes(reader, header_len));
+    let header = match String::from_utf8(header) {
+        Ok(header) => header,
+        Err(_) => return Err(MXError::new("malformed npy header")),
+    };
+    let (dtype, shape) = try!(parse_header(&header));
+
+    let len = shape.iter()
+        .try_fold(dtype.size(), |acc, dim| acc.checked_mul(*dim))
+        .ok_or_else(|| MXError::new(format!("npy array of shape {:?} is too large", shape)));
+    let data = try!(read_bytes(reader, try!(len)));
+    Ok(NpyArray {
+        dtype: dtype,
+        shape: shape,
+        data: data,
+    })
+}
+
+fn write_npy<W: Write>(writer: &mut W, array: &NpyArray) -> MXResult<()> {
+    let mut header = format_header(array.dtype, &array.shape);
+    // Pad the header with spaces so that the data is 64-byte aligned. Format
+    // version 2.0 is only needed when the header length overflows 16 bits.
+    let mut version = 1;
+    let mut preamble_len = MAGIC.len() + 4;
+    if header.len() + 64 > 0xffff {
+        version = 2;
+        preamble_len += 2;
+    }
+    let unpadded = preamble_len + header.len() + 1;
+    for _ in 0..(64 - unpadded % 64) % 64 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    try!(writer.write_all(MAGIC));
+    try!(writer.write_all(&[version, 0]));
+    let header_len = header.len();
+    if version == 1 {
+        try!(writer.write_all(&[header_len as u8, (header_len >> 8) as u8]));
+    } else {
+        try!(writer.write_all(&[header_len as u8,
+                                (header_len >> 8) as u8,
+                                (header_len >> 16) as u8,
+                                (header_len >> 24) as u8]));
+    }
+    try!(writer.write_all(header.as_bytes()));
+    try!(writer.write_all(&array.data));
+    Ok(())
+}
+
+impl NpyArray {
+    fn to_ndarray(&self) -> MXResult<NDArray> {
+        if !cfg!(target_endian = "little") && self.dtype.size() > 1 {
+            return Err(MXError::new("npy arrays require a little-endian host"));
+        }
+        // mxnet has no zero-dimensional arrays, so scalars become 1-element
+        // vectors.
+        let shape = if self.shape.is_empty() {
+            vec![1]
+        } else {
+            self.shape.iter().map(|dim| *dim as u32).collect()
+        };
+        NDArrayBuilder::from_host_bytes(&self.data, self.dtype).shape(shape).create()
+    }
+
+    fn from_ndarray(array: &NDArray) -> MXResult<Self> {
+        let dtype = try!(array.dtype());
+        if !cfg!(target_endian = "little") && dtype.size() > 1 {
+            return Err(MXError::new("npy arrays require a little-endian host"));
+        }
+        Ok(NpyArray {
+            dtype: dtype,
+            shape: array.shape(),
+            data: try!(array.host_bytes()),
+        })
+    }
+}
+
+impl NDArray {
+    /// Load a C-order array from a NumPy `.npy` file into CPU memory.
+    pub fn load_npy(file_name: &str) -> MXResult<Self> {
+        let mut reader = BufReader::new(try!(File::open(file_name)));
+        try!(read_npy(&mut reader)).to_ndarray()
+    }
+
+    /// Save the array to a NumPy `.npy` file.
+    pub fn save_npy(&self, file_name: &str) -> MXResult<()> {
+        let array = try!(NpyArray::from_ndarray(self));
+        let mut writer = BufWriter::new(try!(File::create(file_name)));
+        try!(write_npy(&mut writer, &array));
+        try!(writer.flush());
+        Ok(())
+    }
+
+    /// Load the arrays of a NumPy `.npz` archive, keyed by name, into CPU
+    /// memory. Both `numpy.savez` and `numpy.savez_compressed` archives are
+    /// supported.
+    pub fn load_npz(file_name: &str) -> MXResult<HashMap<String, Self>> {
+        let mut archive = try!(ZipArchive::new(BufReader::new(try!(File::open(file_name)))));
+        let mut map = HashMap::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let mut file = try!(archive.by_index(i));
+            let name = file.name();
+            let name = name.strip_suffix(".npy").unwrap_or(name).to_string();
+            let array = try!(try!(read_npy(&mut file)).to_ndarray());
+            map.insert(name, array);
+        }
+        Ok(map)
+    }
+
+    /// Save arrays to an uncompressed NumPy `.npz` archive, as written by
+    /// `numpy.savez`.
+    pub fn save_npz(file_name: &str, array_map: &HashMap<String, Self>) -> MXResult<()> {
+        let mut writer = ZipWriter::new(BufWriter::new(try!(File::create(file_name))));
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, arr) in array_map.iter() {
+            let array = try!(NpyArray::from_ndarray(arr));
+            try!(writer.start_file(format!("{}.npy", name), options));
+            try!(write_npy(&mut writer, &array));
+        }
+        try!(try!(writer.finish()).flush());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_numpy_header() {
+        let header = "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }";
+        assert_eq!(parse_header(header).unwrap(), (DType::Float32, vec![2, 3]));
+        let header = "{'descr': '|u1', 'fortran_order': False, 'shape': (5,), }";
+        assert_eq!(parse_header(header).unwrap(), (DType::UInt8, vec![5]));
+        let header = "{'descr': '<i8', 'fortran_order': False, 'shape': (), }";
+        assert_eq!(parse_header(header).unwrap(), (DType::Int64, vec![]));
+        let header = "{'descr': '<f8', 'fortran_order': True, 'shape': (2, 2), }";
+        assert!(parse_header(header).is_err());
+        let header = "{'descr': '>f8', 'fortran_order': False, 'shape': (2, 2), }";
+        assert!(parse_header(header).is_err());
+        let header = "{'descr': '\u{e9}f8', 'fortran_order': False, 'shape': (2, 2), }";
+        assert!(parse_header(header).is_err());
+    }
+
+    #[test]
+    fn npy_round_trip() {
+        let array = NpyArray {
+            dtype: DType::Int32,
+            shape: vec![2, 2],
+            data: vec![1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0],
+        };
+        let mut buf = Vec::new();
+        write_npy(&mut buf, &array).unwrap();
+        assert_eq!(&buf[..6], MAGIC);
+        assert_eq!((buf.len() - array.data.len()) % 64, 0);
+        assert_eq!(read_npy(&mut &buf[..]).unwrap(), array);
+    }
+
+    #[test]
+    fn reject_invalid_sizes() {
+        let array = NpyArray {
+            dtype: DType::Float64,
+            shape: vec![1 << 40, 1 << 40],
+            data: Vec::new(),
+        };
+        let mut buf = Vec::new();
+        write_npy(&mut buf, &array).unwrap();
+        assert!(read_npy(&mut &buf[..]).is_err());
+
+        let array = NpyArray {
+            dtype: DType::Float32,
+            shape: vec![1 << 30],
+            data: vec![0; 8],
+        };
+        let mut buf = Vec::new();
+        write_npy(&mut buf, &array).unwrap();
+        assert!(read_npy(&mut &buf[..]).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ptr;
+use std::slice;
+
+use libc::{c_char, c_int};
+use mxnet_sys::*;
+use ndarray::{Context, DType, NDArray};
+use util::*;
+
+lazy_static! {
+    static ref CREATORS: MXResult<HashMap<String, Creator>> = load_creators();
+}
+
+// Force Rust to compile lazy static function handlers.
+struct Creator(AtomicSymbolCreator, OperatorInfo);
+unsafe impl Sync for Creator {}
+
+/// Map every registered operator name, including aliases such as `_plus`, to
+/// its creator.
+fn load_creators() -> MXResult<HashMap<String, Creator>> {
+    let names = try!(list_all_op_names());
+    let mut map = HashMap::with_capacity(names.len());
+    for name in names {
+        let c_name = CString::new(name.as_str()).unwrap();
+        let mut creator = ptr::null_mut();
+        c_try!(NNGetOpHandle(c_name.as_ptr(), &mut creator));
+        let info = try!(OperatorInfo::from_creator(creator));
+        map.insert(name, Creator(creator, info));
+    }
+    Ok(map)
+}
+
+fn find_creator(name: &str) -> MXResult<&'static Creator> {
+    match *CREATORS {
+        Ok(ref creators) => {
+            creators.get(name).ok_or_else(|| MXError::new(format!("unknown operator {}", name)))
+        }
+        Err(ref err) => Err(err.clone()),
+    }
+}
+
+/// Look up the creator of a registered operator by name.
+pub fn get_creator(name: &str) -> MXResult<AtomicSymbolCreator> {
+    Ok(try!(find_creator(name)).0)
+}
+
+/// List the names of all operators registered in mxnet.
+pub fn list_all_op_names() -> MXResult<Vec<String>> {
+    let mut out_size = 0;
+    let mut out_array = ptr::null();
+    c_try!(MXListAllOpNames(&mut out_size, &mut out_array));
+    Ok(c_str_vec(out_array, out_size as usize))
+}
+
+fn c_str_vec(array: *const *const c_char, size: usize) -> Vec<String> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let array_slice = unsafe { slice::from_raw_parts(array, size) };
+    array_slice.iter()
+        .map(|c_str| unsafe { CStr::from_ptr(*c_str) }.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn c_str_string(c_str: *const c_char) -> String {
+    if c_str.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(c_str) }.to_string_lossy().into_owned()
+}
+
+/// Documentation of one operator argument as registered in mxnet.
+#[derive(Debug, Clone)]
+pub struct Argument {
+    pub name: String,
+    pub type_info: String,
+    pub description: String,
+}
+
+/// Documentation of a registered operator.
+#[derive(Debug, Clone)]
+pub struct OperatorInfo {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<Argument>,
+    /// Name of the parameter holding the number of variadic inputs, if any.
+    pub key_var_num_args: Option<String>,
+    pub return_type: String,
+}
+
+impl OperatorInfo {
+    pub fn get(name: &str) -> MXResult<Self> {
+        Ok(try!(find_creator(name)).1.clone())
+    }
+
+    /// Check that the operator accepts the keyword parameter `key`.
+    ///
+    /// Keys of the form `__key__` are attributes rather than parameters, and
+    /// `Custom` operators accept the parameters of the registered operator.
+    fn check_param(&self, key: &str) -> MXResult<()> {
+        let known = self.name == "Custom" || (key.starts_with("__") && key.ends_with("__")) ||
+                    self.key_var_num_args.iter().any(|num_args| num_args == key) ||
+                    self.arguments.iter().any(|arg| arg.name == key);
+        if !known {
+            return Err(MXError::new(format!("operator {} has no parameter {}", self.name, key)));
+        }
+        Ok(())
+    }
+
+    fn from_creator(creator: AtomicSymbolCreator) -> MXResult<Self> {
+        let mut name = ptr::null();
+        let mut description = ptr::null();
+        let mut num_args = 0;
+        let mut arg_names = ptr::null();
+        let mut arg_type_infos = ptr::null();
+        let mut arg_descriptions = ptr::null();
+        let mut key_var_num_args = ptr::null();
+        let mut return_type = ptr::null();
+        c_try!(MXSymbolGetAtomicSymbolInfo(creator,
+                                           &mut name,
+                                           &mut description,
+                                           &mut num_args,
+                                           &mut arg_names,
+                                           &mut arg_type_infos,
+                                           &mut arg_descriptions,
+                                           &mut key_var_num_args,
+                                           &mut return_type));
+        let num_args = num_args as usize;
+        let names = c_str_vec(arg_names, num_args);
+        let type_infos = c_str_vec(arg_type_infos, num_args);
+        let descriptions = c_str_vec(arg_descriptions, num_args);
+        let arguments = names.into_iter()
+            .zip(type_infos.into_iter().zip(descriptions))
+            .map(|(name, (type_info, description))| {
+                Argument {
+                    name: name,
+                    type_info: type_info,
+                    description: description,
+                }
+            })
+            .collect();
+        let key_var_num_args = c_str_string(key_var_num_args);
+        Ok(OperatorInfo {
+            name: c_str_string(name),
+            description: c_str_string(description),
+            arguments: arguments,
+            key_var_num_args: if key_var_num_args.is_empty() {
+                None
+            } else {
+                Some(key_var_num_args)
+            },
+            return_type: c_str_string(return_type),
+        })
+    }
+}
+
+impl fmt::Display for OperatorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f, "{}", self.name));
+        try!(writeln!(f));
+        try!(writeln!(f, "{}", self.description.trim()));
+        if !self.arguments.is_empty() {
+            try!(writeln!(f));
+            try!(writeln!(f, "Arguments:"));
+            for arg in self.arguments.iter() {
+                try!(writeln!(f, "  {} ({}): {}", arg.name, arg.type_info, arg.description));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values that can be passed as operator keyword parameters.
+///
+/// Parameters are handed to mxnet as strings; this trait renders a Rust value
+/// in the syntax the mxnet parameter parser expects.
+pub trait Param {
+    fn to_param(&self) -> String;
+}
+
+macro_rules! param {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl Param for $ty {
+                fn to_param(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+param!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, str, String);
+
+impl Param for bool {
+    fn to_param(&self) -> String {
+        if *self { "True" } else { "False" }.to_string()
+    }
+}
+
+impl Param for DType {
+    fn to_param(&self) -> String {
+        self.name().to_string()
+    }
+}
+
+impl Param for Context {
+    fn to_param(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: Param> Param for [T] {
+    fn to_param(&self) -> String {
+        let items: Vec<String> = self.iter().map(|x| x.to_param()).collect();
+        if items.len() == 1 {
+            format!("({},)", items[0])
+        } else {
+            format!("({})", items.join(","))
+        }
+    }
+}
+
+impl<T: Param> Param for Vec<T> {
+    fn to_param(&self) -> String {
+        self.as_slice().to_param()
+    }
+}
+
+impl<T: Param> Param for Option<T> {
+    fn to_param(&self) -> String {
+        match *self {
+            Some(ref x) => x.to_param(),
+            None => "None".to_string(),
+        }
+    }
+}
+
+impl<'a, T: Param + ?Sized> Param for &'a T {
+    fn to_param(&self) -> String {
+        (**self).to_param()
+    }
+}
+
+/// Imperatively invokes a registered operator on `NDArray` inputs.
+///
+/// ```ignore
+/// let y = try!(Operator::new("clip")
+///     .add_input(&x)
+///     .add_param("a_min", 0.0)
+///     .add_param("a_max", 1.0)
+///     .invoke());
+/// ```
+pub struct Operator<'a> {
+    operator_name: &'a str,
+    inputs: Vec<NDArrayHandle>,
+    param_keys: Vec<CString>,
+    param_values: Vec<CString>,
+}
+
+impl<'a> Operator<'a> {
+    pub fn new(operator_name: &'a str) -> Self {
+        Operator {
+            operator_name: operator_name,
+            inputs: Default::default(),
+            param_keys: Default::default(),
+            param_values: Default::default(),
+        }
+    }
+
+    pub fn add_input(&mut self, input: &'a NDArray) -> &mut Self {
+        self.inputs.push(input.handle);
+        self
+    }
+
+    pub fn add_inputs(&mut self, inputs: &[&'a NDArray]) -> &mut Self {
+        self.inputs.extend(inputs.iter().map(|arr| arr.handle));
+        self
+    }
+
+    /// Add a keyword parameter. Invoking the operator fails if it has no
+    /// parameter named `key`.
+    pub fn add_param<P: Param>(&mut self, key: &str, value: P) -> &mut Self {
+        self.param_keys.push(CString::new(key).unwrap());
+        self.param_values.push(CString::new(value.to_param()).unwrap());
+        self
+    }
+
+    /// Add an input by handle, for in-place invocations where the same array
+    /// is both an input and an output.
+    pub(crate) fn add_input_handle(&mut self, handle: NDArrayHandle) -> &mut Self {
+        self.inputs.push(handle);
+        self
+    }
+
+    /// Invoke the operator and return all of its outputs.
+    pub fn invoke_all(&self) -> MXResult<Vec<NDArray>> {
+        let outputs = try!(self.invoke_raw(Vec::new()));
+        Ok(outputs.into_iter().map(|handle| NDArray { handle: handle }).collect())
+    }
+
+    /// Invoke an operator with a single output.
+    pub fn invoke(&self) -> MXResult<NDArray> {
+        let mut outputs = try!(self.invoke_all());
+        if outputs.len() != 1 {
+            return Err(MXError::new("operator does not have exactly one output"));
+        }
+        Ok(outputs.remove(0))
+    }
+
+    /// Invoke the operator, writing its outputs into existing arrays.
+    pub fn invoke_all_into(&self, outputs: &mut [&mut NDArray]) -> MXResult<()> {
+        try!(self.invoke_raw(outputs.iter().map(|arr| arr.handle).collect()));
+        Ok(())
+    }
+
+    /// Invoke an operator with a single output, writing it into `output`.
+    pub fn invoke_into(&self, output: &mut NDArray) -> MXResult<()> {
+        self.invoke_handle_into(output.handle)
+    }
+
+    pub(crate) fn invoke_handle_into(&self, output: NDArrayHandle) -> MXResult<()> {
+        try!(self.invoke_raw(vec![output]));
+        Ok(())
+    }
+
+    /// Invoke the operator. mxnet allocates the outputs when `outputs` is
+    /// empty and writes into the given arrays otherwise.
+    fn invoke_raw(&self, mut outputs: Vec<NDArrayHandle>) -> MXResult<Vec<NDArrayHandle>> {
+        let &Creator(creator, ref info) = try!(find_creator(self.operator_name));
+        for key in self.param_keys.iter() {
+            try!(info.check_param(&key.to_string_lossy()));
+        }
+        let mut inputs = self.inputs.clone();
+        let param_keys: Vec<*const c_char> = self.param_keys.iter().map(|k| k.as_ptr()).collect();
+        let param_values: Vec<*const c_char> =
+            self.param_values.iter().map(|v| v.as_ptr()).collect();
+        let mut num_outputs = outputs.len() as c_int;
+        let mut outputs_ptr = if outputs.is_empty() {
+            ptr::null_mut()
+        } else {
+            outputs.as_mut_ptr()
+        };
+        c_try!(MXImperativeInvoke(creator,
+                                  inputs.len() as c_int,
+                                  inputs.as_mut_ptr(),
+                                  &mut num_outputs,
+                                  &mut outputs_ptr,
+                                  param_keys.len() as c_int,
+                                  param_keys.as_ptr(),
+                                  param_values.as_ptr()));
+        if outputs.is_empty() {
+            let outputs_slice =
+                unsafe { slice::from_raw_parts(outputs_ptr, num_outputs as usize) };
+            outputs.extend_from_slice(outputs_slice);
+        }
+        Ok(outputs)
+    }
+}
+
+impl NDArray {
+    /// Start an invocation of the operator `operator_name` with this array as
+    /// the first input.
+    pub fn op<'a>(&'a self, operator_name: &'a str) -> Operator<'a> {
+        let mut op = Operator::new(operator_name);
+        op.add_input(self);
+        op
+    }
+}
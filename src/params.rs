@@ -0,0 +1,417 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+use ndarray::{Context, DeviceType, DType, NDArray, NDArrayBuilder};
+use util::*;
+
+/// Magic number of the array list format written by `NDArray::save_list`.
+const NDARRAY_LIST_MAGIC: u64 = 0x112;
+
+const NDARRAY_V1_MAGIC: u32 = 0xF993FAC8;
+const NDARRAY_V2_MAGIC: u32 = 0xF993FAC9;
+const NDARRAY_V3_MAGIC: u32 = 0xF993FACA;
+
+/// Dense storage type of the `NDArray` serialization format.
+const DEFAULT_STORAGE: i32 = 0;
+
+/// An array in the `.params`/`.nd` file format, held in host memory.
+///
+/// Reading and writing these does not require libmxnet. Empty arrays have no
+/// dimensions and no data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawNDArray {
+    pub shape: Vec<usize>,
+    /// The context the array was saved from.
+    pub context: Context,
+    pub dtype: DType,
+    /// Little-endian element data in row-major order.
+    pub data: Vec<u8>,
+}
+
+impl RawNDArray {
+    fn empty() -> Self {
+        RawNDArray {
+            shape: Vec::new(),
+            context: Default::default(),
+            dtype: Default::default(),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Copy an `NDArray` into host memory. Arrays created by `NDArray::new()`
+    /// become empty arrays.
+    pub fn from_ndarray(array: &NDArray) -> MXResult<Self> {
+        if !cfg!(target_endian = "little") {
+            return Err(MXError::new("NDArray files require a little-endian host"));
+        }
+        if array.shape().is_empty() {
+            return Ok(RawNDArray::empty());
+        }
+        Ok(RawNDArray {
+            shape: array.shape(),
+            context: try!(array.context()),
+            dtype: try!(array.dtype()),
+            data: try!(array.host_bytes()),
+        })
+    }
+
+    /// Copy the array into a new `NDArray` on `context`. Empty arrays become
+    /// `NDArray::new()`, as with `NDArray::load`.
+    pub fn to_ndarray(&self, context: Context) -> MXResult<NDArray> {
+        if !cfg!(target_endian = "little") {
+            return Err(MXError::new("NDArray files require a little-endian host"));
+        }
+        if self.shape.is_empty() && self.data.is_empty() {
+            return NDArray::new();
+        }
+        NDArrayBuilder::from_host_bytes(&self.data, self.dtype)
+            .shape(self.shape.iter().map(|dim| *dim as u32).collect())
+            .context(context)
+            .create()
+    }
+}
+
+/// The length in bytes of the data of an array, or an error on overflow.
+fn data_len(shape: &[usize], dtype: DType) -> MXResult<usize> {
+    shape.iter()
+        .try_fold(dtype.size(), |acc, dim| acc.checked_mul(*dim))
+        .ok_or_else(|| MXError::new(format!("NDArray of shape {:?} is too large", shape)))
+}
+
+fn write_u64(buf: &mut Vec<u8>, value: u64) {
+    for i in 0..8 {
+        buf.push((value >> (8 * i)) as u8);
+    }
+}
+
+fn write_u32(buf: &mut Vec<u8>, value: u32) {
+    for i in 0..4 {
+        buf.push((value >> (8 * i)) as u8);
+    }
+}
+
+fn write_i32(buf: &mut Vec<u8>, value: i32) {
+    write_u32(buf, value as u32)
+}
+
+fn write_i64(buf: &mut Vec<u8>, value: i64) {
+    write_u64(buf, value as u64)
+}
+
+/// Reads little-endian values from a byte buffer.
+struct Cursor<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn bytes(&mut self, len: usize) -> MXResult<&'a [u8]> {
+        if len > self.buf.len() {
+            return Err(MXError::new("unexpected end of NDArray file"));
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> MXResult<u64> {
+        let bytes = try!(self.bytes(8));
+        Ok(bytes.iter().rev().fold(0, |acc, x| acc << 8 | *x as u64))
+    }
+
+    fn u32(&mut self) -> MXResult<u32> {
+        let bytes = try!(self.bytes(4));
+        Ok(bytes.iter().rev().fold(0, |acc, x| acc << 8 | *x as u32))
+    }
+
+    fn i32(&mut self) -> MXResult<i32> {
+        Ok(try!(self.u32()) as i32)
+    }
+
+    fn i64(&mut self) -> MXResult<i64> {
+        Ok(try!(self.u64()) as i64)
+    }
+
+    fn len(&mut self) -> MXResult<usize> {
+        let len = try!(self.u64());
+        if len > self.buf.len() as u64 {
+            return Err(MXError::new("unexpected end of NDArray file"));
+        }
+        Ok(len as usize)
+    }
+
+    fn shape(&mut self, ndim: usize, wide: bool) -> MXResult<Vec<usize>> {
+        // Bound the allocation by the dimensions left in the buffer.
+        let width = if wide { 8 } else { 4 };
+        if ndim > self.buf.len() / width {
+            return Err(MXError::new("unexpected end of NDArray file"));
+        }
+        let mut shape = Vec::with_capacity(ndim);
+        for _ in 0..ndim {
+            let dim = if wide {
+                try!(self.i64())
+            } else {
+                try!(self.u32()) as i64
+            };
+            if dim < 0 {
+                return Err(MXError::new("unknown dimension in NDArray file"));
+            }
+            shape.push(dim as usize);
+        }
+        Ok(shape)
+    }
+
+    fn ndarray(&mut self) -> MXResult<RawNDArray> {
+        let magic = try!(self.u32());
+        let shape = match magic {
+            NDARRAY_V2_MAGIC | NDARRAY_V3_MAGIC => {
+                if try!(self.i32()) != DEFAULT_STORAGE {
+                    return Err(MXError::new("sparse NDArrays are not supported"));
+                }
+                // Version 3 marks empty arrays with -1 dimensions, so that
+                // zero dimensions describe a scalar.
+                match try!(self.i32()) {
+                    -1 => None,
+                    ndim if ndim < 0 => return Err(MXError::new("invalid NDArray shape")),
+                    ndim => Some(try!(self.shape(ndim as usize, true))),
+                }
+            }
+            NDARRAY_V1_MAGIC => {
+                let ndim = try!(self.u32());
+                Some(try!(self.shape(ndim as usize, true)))
+            }
+            // Files from before the format had a magic number start with the
+            // number of dimensions, followed by 32-bit dimensions.
+            ndim => Some(try!(self.shape(ndim as usize, false))),
+        };
+        let shape = match shape.filter(|shape| !shape.is_empty() || magic == NDARRAY_V3_MAGIC) {
+            Some(shape) => shape,
+            None => return Ok(RawNDArray::empty()),
+        };
+
+        let dev_type = try!(DeviceType::from_raw(try!(self.i32())));
+        let dev_id = try!(self.i32());
+        let dtype = try!(DType::from_raw(try!(self.i32())));
+        let len = try!(data_len(&shape, dtype));
+        let data = try!(self.bytes(len)).to_vec();
+        Ok(RawNDArray {
+            shape: shape,
+            context: Context::new(dev_type, dev_id as isize),
+            dtype: dtype,
+            data: data,
+        })
+    }
+}
+
+fn write_ndarray(buf: &mut Vec<u8>, array: &RawNDArray) -> MXResult<()> {
+    write_u32(buf, NDARRAY_V2_MAGIC);
+    write_i32(buf, DEFAULT_STORAGE);
+    write_i32(buf, array.shape.len() as i32);
+    for dim in array.shape.iter() {
+        write_i64(buf, *dim as i64);
+    }
+    if array.shape.is_empty() {
+        if !array.data.is_empty() {
+            return Err(MXError::new("zero-dimensional NDArrays are not supported"));
+        }
+        return Ok(());
+    }
+    if array.data.len() != try!(data_len(&array.shape, array.dtype)) {
+        return Err(MXError::new("NDArray size mismatch"));
+    }
+    write_i32(buf, array.context.device_type as i32);
+    write_i32(buf, array.context.device_id as i32);
+    write_i32(buf, array.dtype as i32);
+    buf.extend_from_slice(&array.data);
+    Ok(())
+}
+
+/// Frame serialized arrays, and optionally their names, as a list: a magic
+/// number and a reserved `u64`, followed by a `u64`-length-prefixed list of
+/// arrays and a `u64`-length-prefixed list of length-prefixed names.
+pub(crate) fn write_list<A, F, S>(arrays: &[A],
+                                  names: &[S],
+                                  mut write_array: F)
+                                  -> MXResult<Vec<u8>>
+    where F: FnMut(&mut Vec<u8>, &A) -> MXResult<()>,
+          S: AsRef<str>
+{
+    if !names.is_empty() && names.len() != arrays.len() {
+        return Err(MXError::new("NDArray save with names size mismatch"));
+    }
+    let mut buf = Vec::new();
+    write_u64(&mut buf, NDARRAY_LIST_MAGIC);
+    write_u64(&mut buf, 0);
+    write_u64(&mut buf, arrays.len() as u64);
+    for array in arrays {
+        try!(write_array(&mut buf, array));
+    }
+    write_u64(&mut buf, names.len() as u64);
+    for name in names {
+        let name = name.as_ref();
+        write_u64(&mut buf, name.len() as u64);
+        buf.extend_from_slice(name.as_bytes());
+    }
+    Ok(buf)
+}
+
+/// Parse arrays, and their names if present, from a buffer in the format
+/// written by `NDArray::save_list` and `NDArray::save_map`.
+pub fn load_bytes(buf: &[u8]) -> MXResult<(Vec<RawNDArray>, Option<Vec<String>>)> {
+    let mut cursor = Cursor { buf: buf };
+    if try!(cursor.u64()) != NDARRAY_LIST_MAGIC {
+        return Err(MXError::new("invalid NDArray file format"));
+    }
+    try!(cursor.u64());
+
+    let num_arrays = try!(cursor.len());
+    let mut arrays = Vec::with_capacity(num_arrays);
+    for _ in 0..num_arrays {
+        arrays.push(try!(cursor.ndarray()));
+    }
+
+    let num_names = try!(cursor.len());
+    if num_names == 0 {
+        return Ok((arrays, None));
+    }
+    if num_names != num_arrays {
+        return Err(MXError::new("NDArray load with names size mismatch"));
+    }
+    let mut names = Vec::with_capacity(num_names);
+    for _ in 0..num_names {
+        let len = try!(cursor.len());
+        let name = try!(cursor.bytes(len));
+        names.push(String::from_utf8_lossy(name).into_owned());
+    }
+    Ok((arrays, Some(names)))
+}
+
+/// Serialize arrays, and optionally their names, into the format written by
+/// `NDArray::save_list` and `NDArray::save_map`.
+pub fn save_bytes(arrays: &[RawNDArray], names: Option<&[String]>) -> MXResult<Vec<u8>> {
+    write_list(arrays, names.unwrap_or(&[]), write_ndarray)
+}
+
+pub fn read<R: Read>(reader: &mut R) -> MXResult<(Vec<RawNDArray>, Option<Vec<String>>)> {
+    let mut buf = Vec::new();
+    try!(reader.read_to_end(&mut buf));
+    load_bytes(&buf)
+}
+
+pub fn write<W: Write>(writer: &mut W,
+                       arrays: &[RawNDArray],
+                       names: Option<&[String]>)
+                       -> MXResult<()> {
+    try!(writer.write_all(&try!(save_bytes(arrays, names))));
+    Ok(())
+}
+
+pub fn load(file_name: &str) -> MXResult<(Vec<RawNDArray>, Option<Vec<String>>)> {
+    read(&mut BufReader::new(try!(File::open(file_name))))
+}
+
+pub fn save(file_name: &str, arrays: &[RawNDArray], names: Option<&[String]>) -> MXResult<()> {
+    let mut writer = BufWriter::new(try!(File::create(file_name)));
+    try!(write(&mut writer, arrays, names));
+    try!(writer.flush());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let arrays = vec![RawNDArray {
+                              shape: vec![2, 2],
+                              context: Context::gpu(1),
+                              dtype: DType::Float32,
+                              data: vec![0; 16],
+                          },
+                          RawNDArray {
+                              shape: vec![3],
+                              context: Context::cpu(0),
+                              dtype: DType::UInt8,
+                              data: vec![1, 2, 3],
+                          }];
+        let names = vec!["arg:weight".to_string(), "arg:bias".to_string()];
+        let buf = save_bytes(&arrays, Some(&names)).unwrap();
+        assert_eq!(load_bytes(&buf).unwrap(), (arrays.clone(), Some(names)));
+        let buf = save_bytes(&arrays, None).unwrap();
+        assert_eq!(load_bytes(&buf).unwrap(), (arrays, None));
+    }
+
+    #[test]
+    fn load_legacy_format() {
+        let mut buf = Vec::new();
+        write_u64(&mut buf, NDARRAY_LIST_MAGIC);
+        write_u64(&mut buf, 0);
+        write_u64(&mut buf, 1);
+        write_u32(&mut buf, 1);
+        write_u32(&mut buf, 2);
+        write_i32(&mut buf, 1);
+        write_i32(&mut buf, 0);
+        write_i32(&mut buf, DType::Int8 as i32);
+        buf.extend_from_slice(&[7, 8]);
+        write_u64(&mut buf, 0);
+        let (arrays, names) = load_bytes(&buf).unwrap();
+        assert_eq!(arrays,
+                   vec![RawNDArray {
+                            shape: vec![2],
+                            context: Context::cpu(0),
+                            dtype: DType::Int8,
+                            data: vec![7, 8],
+                        }]);
+        assert_eq!(names, None);
+    }
+
+    #[test]
+    fn reject_truncated() {
+        let arrays = vec![RawNDArray {
+                              shape: vec![4],
+                              context: Context::cpu(0),
+                              dtype: DType::Float64,
+                              data: vec![0; 32],
+                          }];
+        let buf = save_bytes(&arrays, None).unwrap();
+        assert!(load_bytes(&buf[..buf.len() - 9]).is_err());
+    }
+
+    #[test]
+    fn empty_ndarray_round_trip() {
+        let array = RawNDArray::from_ndarray(&NDArray::new().unwrap()).unwrap();
+        assert_eq!(array, RawNDArray::empty());
+        assert!(array.to_ndarray(Context::cpu(0)).unwrap().shape().is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_sizes() {
+        // A legacy array claiming more dimensions than the file holds.
+        let mut buf = Vec::new();
+        write_u64(&mut buf, NDARRAY_LIST_MAGIC);
+        write_u64(&mut buf, 0);
+        write_u64(&mut buf, 1);
+        write_u32(&mut buf, 0x7fff_ffff);
+        write_u64(&mut buf, 0);
+        assert!(load_bytes(&buf).is_err());
+
+        let mut buf = Vec::new();
+        write_u64(&mut buf, NDARRAY_LIST_MAGIC);
+        write_u64(&mut buf, 0);
+        write_u64(&mut buf, 1);
+        write_u32(&mut buf, NDARRAY_V2_MAGIC);
+        write_i32(&mut buf, DEFAULT_STORAGE);
+        write_i32(&mut buf, 2);
+        write_i64(&mut buf, 1 << 40);
+        write_i64(&mut buf, 1 << 40);
+        write_i32(&mut buf, 1);
+        write_i32(&mut buf, 0);
+        write_i32(&mut buf, DType::Float32 as i32);
+        write_u64(&mut buf, 0);
+        assert!(load_bytes(&buf).is_err());
+    }
+}
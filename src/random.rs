@@ -0,0 +1,71 @@
+use ndarray::{Context, NDArray};
+use operator::Operator;
+use util::*;
+
+/// Samples from the uniform distribution on `[low, high)`.
+pub fn uniform(low: f64, high: f64, shape: &[usize], context: Context) -> MXResult<NDArray> {
+    Operator::new("_random_uniform")
+        .add_param("low", low)
+        .add_param("high", high)
+        .add_param("shape", shape)
+        .add_param("ctx", context)
+        .invoke()
+}
+
+/// Samples from the normal distribution with mean `loc` and standard
+/// deviation `scale`.
+pub fn normal(loc: f64, scale: f64, shape: &[usize], context: Context) -> MXResult<NDArray> {
+    Operator::new("_random_normal")
+        .add_param("loc", loc)
+        .add_param("scale", scale)
+        .add_param("shape", shape)
+        .add_param("ctx", context)
+        .invoke()
+}
+
+/// Samples from the gamma distribution with shape `alpha` and scale `beta`.
+pub fn gamma(alpha: f64, beta: f64, shape: &[usize], context: Context) -> MXResult<NDArray> {
+    Operator::new("_random_gamma")
+        .add_param("alpha", alpha)
+        .add_param("beta", beta)
+        .add_param("shape", shape)
+        .add_param("ctx", context)
+        .invoke()
+}
+
+/// Samples from the exponential distribution with rate `lam`.
+pub fn exponential(lam: f64, shape: &[usize], context: Context) -> MXResult<NDArray> {
+    Operator::new("_random_exponential")
+        .add_param("lam", lam)
+        .add_param("shape", shape)
+        .add_param("ctx", context)
+        .invoke()
+}
+
+/// Samples from the Poisson distribution with rate `lam`.
+pub fn poisson(lam: f64, shape: &[usize], context: Context) -> MXResult<NDArray> {
+    Operator::new("_random_poisson")
+        .add_param("lam", lam)
+        .add_param("shape", shape)
+        .add_param("ctx", context)
+        .invoke()
+}
+
+/// Samples indices from the categorical distributions in the last axis of
+/// `data`, drawing `shape` samples from each distribution. The result has
+/// data type `int32` and is placed in the context of `data`.
+pub fn multinomial(data: &NDArray, shape: &[usize]) -> MXResult<NDArray> {
+    let mut op = Operator::new("_sample_multinomial");
+    op.add_input(data);
+    if !shape.is_empty() {
+        op.add_param("shape", shape);
+    }
+    op.invoke()
+}
+
+/// Randomly shuffle the elements of `data` along the first axis in place.
+pub fn shuffle(data: &mut NDArray) -> MXResult<()> {
+    Operator::new("_shuffle")
+        .add_input_handle(data.handle)
+        .invoke_handle_into(data.handle)
+}
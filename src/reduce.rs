@@ -0,0 +1,96 @@
+use ndarray::NDArray;
+use util::*;
+
+macro_rules! reduce {
+    ( $( $op:ident, $op_scalar:ident, $doc:expr; )* ) => {
+        impl NDArray {
+            $(
+                #[doc = $doc]
+                ///
+                /// Reduces over all axes when `axis` is empty. Reduced axes are
+                /// kept with size one if `keepdims` is set.
+                pub fn $op(&self, axis: &[usize], keepdims: bool) -> MXResult<NDArray> {
+                    self.reduce(stringify!($op), axis, keepdims)
+                }
+
+                #[doc = $doc]
+                ///
+                /// Reduces over all elements and returns the result as `f32`.
+                pub fn $op_scalar(&self) -> MXResult<f32> {
+                    try!(self.$op(&[], false)).to_scalar()
+                }
+            )*
+        }
+    };
+}
+
+reduce! {
+    sum, sum_scalar, "The sum of the elements along `axis`.";
+    mean, mean_scalar, "The mean of the elements along `axis`.";
+    prod, prod_scalar, "The product of the elements along `axis`.";
+    max, max_scalar, "The maximum of the elements along `axis`.";
+    min, min_scalar, "The minimum of the elements along `axis`.";
+}
+
+impl NDArray {
+    fn reduce(&self, operator_name: &str, axis: &[usize], keepdims: bool) -> MXResult<NDArray> {
+        for a in axis {
+            try!(self.check_axis(*a));
+        }
+        let mut op = self.op(operator_name);
+        op.add_param("keepdims", keepdims);
+        if !axis.is_empty() {
+            op.add_param("axis", axis);
+        }
+        op.invoke()
+    }
+
+    fn arg_reduce(&self,
+                  operator_name: &str,
+                  axis: Option<usize>,
+                  keepdims: bool)
+                  -> MXResult<NDArray> {
+        if let Some(axis) = axis {
+            try!(self.check_axis(axis));
+        }
+        self.op(operator_name)
+            .add_param("axis", axis)
+            .add_param("keepdims", keepdims)
+            .invoke()
+    }
+
+    /// The indices of the maximum elements along `axis`, or in the flattened
+    /// array if `axis` is `None`. Indices are returned as floating point.
+    pub fn argmax(&self, axis: Option<usize>, keepdims: bool) -> MXResult<NDArray> {
+        self.arg_reduce("argmax", axis, keepdims)
+    }
+
+    /// The indices of the minimum elements along `axis`, or in the flattened
+    /// array if `axis` is `None`. Indices are returned as floating point.
+    pub fn argmin(&self, axis: Option<usize>, keepdims: bool) -> MXResult<NDArray> {
+        self.arg_reduce("argmin", axis, keepdims)
+    }
+
+    /// The L1 (`ord == 1`) or L2 (`ord == 2`) norm along `axis`.
+    ///
+    /// Reduces over all axes when `axis` is empty.
+    pub fn norm(&self, ord: u32, axis: &[usize], keepdims: bool) -> MXResult<NDArray> {
+        if ord != 1 && ord != 2 {
+            return Err(MXError::new("NDArray norm order must be 1 or 2"));
+        }
+        for a in axis {
+            try!(self.check_axis(*a));
+        }
+        let mut op = self.op("norm");
+        op.add_param("ord", ord).add_param("keepdims", keepdims);
+        if !axis.is_empty() {
+            op.add_param("axis", axis);
+        }
+        op.invoke()
+    }
+
+    /// The L1 (`ord == 1`) or L2 (`ord == 2`) norm of all elements as `f32`.
+    pub fn norm_scalar(&self, ord: u32) -> MXResult<f32> {
+        try!(self.norm(ord, &[], false)).to_scalar()
+    }
+}
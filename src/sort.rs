@@ -0,0 +1,103 @@
+use operator::Param;
+use ndarray::{DType, NDArray};
+use util::*;
+
+/// What `NDArray::topk` returns.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TopKOutput {
+    /// The top `k` values.
+    Value,
+    /// The indices of the top `k` values.
+    Indices,
+    /// An array of the input shape that is one at the top `k` values and zero
+    /// elsewhere.
+    Mask,
+}
+
+impl Param for TopKOutput {
+    fn to_param(&self) -> String {
+        match *self {
+            TopKOutput::Value => "value",
+            TopKOutput::Indices => "indices",
+            TopKOutput::Mask => "mask",
+        }
+        .to_string()
+    }
+}
+
+impl NDArray {
+    /// Sort the elements along `axis`.
+    pub fn sort(&self, axis: usize, ascending: bool) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        self.op("sort")
+            .add_param("axis", axis)
+            .add_param("is_ascend", ascending)
+            .invoke()
+    }
+
+    /// The indices that sort the elements along `axis`, as an array of
+    /// `dtype`.
+    pub fn argsort(&self, axis: usize, ascending: bool, dtype: DType) -> MXResult<NDArray> {
+        try!(self.check_axis(axis));
+        self.op("argsort")
+            .add_param("axis", axis)
+            .add_param("is_ascend", ascending)
+            .add_param("dtype", dtype)
+            .invoke()
+    }
+
+    fn check_topk(&self, axis: usize, k: usize) -> MXResult<()> {
+        try!(self.check_axis(axis));
+        let dim = self.shape()[axis];
+        if k == 0 || k > dim {
+            return Err(MXError::new(format!("cannot select top {} elements of axis of size {}",
+                                            k,
+                                            dim)));
+        }
+        Ok(())
+    }
+
+    /// Select the `k` largest elements along `axis`, or the smallest if
+    /// `ascending` is set. Indices and masks are returned as arrays of `dtype`.
+    pub fn topk(&self,
+                axis: usize,
+                k: usize,
+                output: TopKOutput,
+                ascending: bool,
+                dtype: DType)
+                -> MXResult<NDArray> {
+        try!(self.check_topk(axis, k));
+        self.op("topk")
+            .add_param("axis", axis)
+            .add_param("k", k)
+            .add_param("ret_typ", output)
+            .add_param("is_ascend", ascending)
+            .add_param("dtype", dtype)
+            .invoke()
+    }
+
+    /// Select the `k` largest elements along `axis`, or the smallest if
+    /// `ascending` is set, returning both the values and their indices as an
+    /// array of `dtype`.
+    pub fn topk_both(&self,
+                     axis: usize,
+                     k: usize,
+                     ascending: bool,
+                     dtype: DType)
+                     -> MXResult<(NDArray, NDArray)> {
+        try!(self.check_topk(axis, k));
+        let mut outputs = try!(self.op("topk")
+            .add_param("axis", axis)
+            .add_param("k", k)
+            .add_param("ret_typ", "both")
+            .add_param("is_ascend", ascending)
+            .add_param("dtype", dtype)
+            .invoke_all());
+        if outputs.len() != 2 {
+            return Err(MXError::new("NDArray topk returned an unexpected number of outputs"));
+        }
+        let indices = outputs.pop().unwrap();
+        let values = outputs.pop().unwrap();
+        Ok((values, indices))
+    }
+}
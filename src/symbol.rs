@@ -1,56 +1,19 @@
-use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::ops;
 use std::ptr;
-use std::slice;
 
 use libc::{c_char, c_int, c_uint, c_void};
 use mxnet_sys::*;
+use operator::get_creator;
 use util::*;
 
-lazy_static! {
-    static ref SYMBOL_CREATORS: HashMap<String, Creator> = {
-        let mut num_symbol_creators = 0;
-        let mut symbol_creators = ptr::null_mut();
-        c_must!(MXSymbolListAtomicSymbolCreators(&mut num_symbol_creators, &mut symbol_creators));
-        let symbol_creators_slice =
-            unsafe { slice::from_raw_parts(symbol_creators, num_symbol_creators as usize) };
-        let mut map = HashMap::with_capacity(num_symbol_creators as usize);
-        for symbol_creator in symbol_creators_slice {
-            let mut name = ptr::null();
-            let mut description = ptr::null();
-            let mut num_args = 0;
-            let mut arg_names = ptr::null();
-            let mut arg_type_infos = ptr::null();
-            let mut arg_descriptions = ptr::null();
-            let mut key_var_num_args = ptr::null();
-            c_must!(MXSymbolGetAtomicSymbolInfo(*symbol_creator,
-                                                &mut name,
-                                                &mut description,
-                                                &mut num_args,
-                                                &mut arg_names,
-                                                &mut arg_type_infos,
-                                                &mut arg_descriptions,
-                                                &mut key_var_num_args,
-                                                ptr::null_mut()));
-            let name = unsafe { CStr::from_ptr(name) };
-            map.insert(name.to_string_lossy().into_owned(), Creator(*symbol_creator));
-        }
-        map
-    };
-}
-
-// Force Rust to compile lazy static function handlers.
-struct Creator(AtomicSymbolCreator);
-unsafe impl Sync for Creator {}
-
 #[derive(Debug)]
 pub struct SymbolBuilder<'a> {
     operator_name: &'a str,
-    input_keys: Vec<*const c_char>,
+    input_keys: Vec<CString>,
     input_values: Vec<SymbolHandle>,
-    param_keys: Vec<*const c_char>,
-    param_values: Vec<*const c_char>,
+    param_keys: Vec<CString>,
+    param_values: Vec<CString>,
 }
 
 impl<'a> SymbolBuilder<'a> {
@@ -65,7 +28,7 @@ impl<'a> SymbolBuilder<'a> {
     }
 
     pub fn add_input(&mut self, key: &str, value: &Symbol) -> &mut Self {
-        self.input_keys.push(CString::new(key).unwrap().as_ptr());
+        self.input_keys.push(CString::new(key).unwrap());
         self.input_values.push(value.handle);
         self
     }
@@ -77,31 +40,37 @@ impl<'a> SymbolBuilder<'a> {
     }
 
     pub fn add_param(&mut self, key: &str, value: &str) -> &mut Self {
-        self.param_keys.push(CString::new(key).unwrap().as_ptr());
-        self.param_values.push(CString::new(value).unwrap().as_ptr());
+        self.param_keys.push(CString::new(key).unwrap());
+        self.param_values.push(CString::new(value).unwrap());
         self
     }
 
     fn create_symbol(&self) -> MXResult<Symbol> {
-        let symbol_creator = SYMBOL_CREATORS.get(self.operator_name).unwrap().0;
-        let num_param = self.param_keys.len() as u32;
-        let param_keys = self.param_keys.as_ptr();
-        let param_values = self.param_keys.as_ptr();
+        let symbol_creator = try!(get_creator(self.operator_name));
+        let param_keys: Vec<*const c_char> = self.param_keys.iter().map(|k| k.as_ptr()).collect();
+        let param_values: Vec<*const c_char> =
+            self.param_values.iter().map(|v| v.as_ptr()).collect();
         let mut handle = ptr::null_mut();
         c_try!(MXSymbolCreateAtomicSymbol(symbol_creator,
-                                          num_param,
-                                          param_keys,
-                                          param_values,
+                                          param_keys.len() as u32,
+                                          param_keys.as_ptr(),
+                                          param_values.as_ptr(),
                                           &mut handle));
         Ok(Symbol { handle: handle })
     }
 
     fn compose_symbol(&self, symbol: &mut Symbol, name: &str) -> MXResult<()> {
-        let name = CString::new(name).unwrap().as_ptr();
-        let num_input = self.input_keys.len() as u32;
-        let input_keys = self.input_keys.as_ptr();
+        let name = CString::new(name).unwrap();
+        let num_input = self.input_values.len() as u32;
+        // Inputs set with `set_input` are positional and have no keys.
+        let keys: Vec<*const c_char> = self.input_keys.iter().map(|k| k.as_ptr()).collect();
+        let input_keys = if keys.is_empty() {
+            ptr::null()
+        } else {
+            keys.as_ptr()
+        };
         let input_values = self.input_values.as_ptr();
-        c_try!(MXSymbolCompose(symbol.handle, name, num_input, input_keys, input_values));
+        c_try!(MXSymbolCompose(symbol.handle, name.as_ptr(), num_input, input_keys, input_values));
         Ok(())
     }
 
@@ -3,10 +3,12 @@ use libc::c_int;
 use std::error;
 use std::ffi::{CStr, CString};
 use std::fmt;
-use std::ptr;
+use std::io;
 use std::str;
 
 use mxnet_sys::*;
+use zip::result::ZipError;
+use ndarray::Context;
 
 #[macro_export]
 macro_rules! c_must {
@@ -29,20 +31,20 @@ macro_rules! c_try {
     }};
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MXError {
-    errmsg: &'static str,
+    errmsg: String,
 }
 
 impl MXError {
-    pub fn new(errmsg: &'static str) -> Self {
-        MXError { errmsg: errmsg }
+    pub fn new<S: Into<String>>(errmsg: S) -> Self {
+        MXError { errmsg: errmsg.into() }
     }
 }
 
 impl error::Error for MXError {
     fn description(&self) -> &str {
-        self.errmsg
+        &self.errmsg
     }
 }
 
@@ -52,6 +54,18 @@ impl fmt::Display for MXError {
     }
 }
 
+impl From<io::Error> for MXError {
+    fn from(err: io::Error) -> Self {
+        MXError::new(err.to_string())
+    }
+}
+
+impl From<ZipError> for MXError {
+    fn from(err: ZipError) -> Self {
+        MXError::new(err.to_string())
+    }
+}
+
 pub type MXResult<T> = Result<T, MXError>;
 
 pub fn get_last_error() -> &'static str {
@@ -59,15 +73,25 @@ pub fn get_last_error() -> &'static str {
     errmsg.to_str().unwrap()
 }
 
+/// Set the message returned by `get_last_error`, e.g. for an error in a
+/// callback from mxnet.
+pub(crate) fn set_last_error(errmsg: &str) {
+    if let Ok(c_errmsg) = CString::new(errmsg) {
+        unsafe { NNAPISetLastError(c_errmsg.as_ptr()) };
+    }
+}
+
 pub fn error_result<T>() -> MXResult<T> {
     Err(MXError::new(get_last_error()))
 }
 
-pub fn get_function(name: &'static str) -> FunctionHandle {
-    let mut func_handle = ptr::null();
-    let c_str = CString::new(name).unwrap();
-    c_must!(MXGetFunction(c_str.as_ptr(), &mut func_handle));
-    func_handle
+/// Unwrap the result of an operation that cannot report errors, such as an
+/// operator trait implementation, panicking with the mxnet error message.
+pub fn must<T>(result: MXResult<T>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => panic!("{}", err),
+    }
 }
 
 /// Seed the global random number generators in mxnet.
@@ -75,6 +99,22 @@ pub fn random_seed(seed: isize) -> MXResult<()> {
     c_try!(MXRandomSeed(seed as c_int), Ok(()))
 }
 
+/// Seed the random number generators of a single device in mxnet, leaving
+/// the generators of other devices untouched.
+pub fn random_seed_context(seed: isize, context: Context) -> MXResult<()> {
+    c_try!(MXRandomSeedContext(seed as c_int,
+                               context.device_type as c_int,
+                               context.device_id as c_int),
+           Ok(()))
+}
+
+/// Block until all pending engine operations have finished.
+///
+/// Errors raised asynchronously by those operations are returned here.
+pub fn wait_all() -> MXResult<()> {
+    c_try!(MXNDArrayWaitAll(), Ok(()))
+}
+
 /// Notify the engine about a shutdown.
 ///
 /// This can help engine to print fewer console messages. It is not necessary